@@ -0,0 +1,60 @@
+use crate::common::{Number, UnsignedInteger};
+use crate::BellmanFr;
+
+use bellman::gadgets::boolean::Boolean;
+use bellman::{ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+// Enforces sum(inputs) == sum(outputs) for every token id that appears in either
+// list (Fees should be passed as outputs). Token ids may be mixed freely, the
+// amounts of each token are selected by masking them with `is_equal` on the token
+// ids, so a token that only appears in the outputs must have zero output amount.
+// Amounts are range checked by their UnsignedInteger type, and the sums can not
+// wrap around the field. For n entries in total: 2 * n * (n - 1) + n constraints.
+pub fn assert_balanced<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    inputs: &[(Number, UnsignedInteger)],
+    outputs: &[(Number, UnsignedInteger)],
+) -> Result<(), SynthesisError> {
+    let entries: Vec<(&Number, &UnsignedInteger, bool)> = inputs
+        .iter()
+        .map(|(token_id, amount)| (token_id, amount, true))
+        .chain(
+            outputs
+                .iter()
+                .map(|(token_id, amount)| (token_id, amount, false)),
+        )
+        .collect();
+    let n = entries.len();
+    let max_bits = entries.iter().map(|e| e.1.num_bits()).max().unwrap_or(0);
+    let log_n = (usize::BITS - n.leading_zeros()) as usize;
+    assert!(max_bits + log_n < BellmanFr::CAPACITY as usize);
+
+    let mut same_token = vec![vec![Boolean::Constant(true); n]; n];
+    for i in 0..n {
+        for j in i + 1..n {
+            let eq = entries[i].0.is_equal(&mut *cs, entries[j].0)?;
+            same_token[i][j] = eq.clone();
+            same_token[j][i] = eq;
+        }
+    }
+
+    // Conservation of the token of the i-th entry
+    for same in same_token.iter() {
+        let mut diff = Number::zero();
+        for (is_same, (_, amount, is_input)) in same.iter().zip(entries.iter()) {
+            let masked = Number::from(is_same.clone()).mul_number(&mut *cs, amount.get_number())?;
+            diff = if *is_input {
+                diff + masked
+            } else {
+                diff - masked
+            };
+        }
+        diff.assert_equal(&mut *cs, &Number::zero());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test;
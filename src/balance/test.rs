@@ -0,0 +1,119 @@
+use super::*;
+use crate::Bls12;
+use bellman::gadgets::num::AllocatedNum;
+use bellman::{groth16, Circuit};
+use rand::rngs::OsRng;
+
+#[derive(Clone)]
+struct TestBalanceCircuit {
+    inputs: Vec<(Option<BellmanFr>, Option<BellmanFr>)>,
+    outputs: Vec<(Option<BellmanFr>, Option<BellmanFr>)>,
+}
+
+impl Circuit<BellmanFr> for TestBalanceCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let mut alloc = |entries: &[(Option<BellmanFr>, Option<BellmanFr>)]| {
+            let mut res = Vec::new();
+            for (token_id, amount) in entries.iter() {
+                let token_id = AllocatedNum::alloc(&mut *cs, || {
+                    token_id.ok_or(SynthesisError::AssignmentMissing)
+                })?;
+                let amount = AllocatedNum::alloc(&mut *cs, || {
+                    amount.ok_or(SynthesisError::AssignmentMissing)
+                })?;
+                let amount = UnsignedInteger::constrain(&mut *cs, amount.into(), 64)?;
+                res.push((Number::from(token_id), amount));
+            }
+            Ok::<_, SynthesisError>(res)
+        };
+        let inputs = alloc(&self.inputs)?;
+        let outputs = alloc(&self.outputs)?;
+
+        assert_balanced(&mut *cs, &inputs, &outputs)?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_balance_circuit() {
+    let params = {
+        let c = TestBalanceCircuit {
+            inputs: vec![(None, None); 3],
+            outputs: vec![(None, None); 3],
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    let max = u64::MAX;
+    for (inputs, outputs, expected) in [
+        // Single token, the last output is the fee
+        (
+            [(1, 100), (1, 50), (1, 0)],
+            [(1, 120), (1, 25), (1, 5)],
+            true,
+        ),
+        (
+            [(1, 100), (1, 50), (1, 0)],
+            [(1, 120), (1, 25), (1, 6)],
+            false,
+        ),
+        // Mixed tokens
+        (
+            [(1, 100), (2, 50), (1, 10)],
+            [(2, 50), (1, 109), (1, 1)],
+            true,
+        ),
+        (
+            [(1, 100), (2, 50), (1, 10)],
+            [(2, 51), (1, 108), (1, 1)],
+            false,
+        ),
+        (
+            [(1, 100), (2, 50), (3, 10)],
+            [(3, 10), (2, 50), (1, 100)],
+            true,
+        ),
+        // Minting a token that is not an input
+        (
+            [(1, 100), (2, 50), (3, 10)],
+            [(3, 10), (2, 50), (4, 100)],
+            false,
+        ),
+        (
+            [(1, 100), (2, 50), (3, 10)],
+            [(3, 10), (2, 50), (4, 0)],
+            false,
+        ),
+        (
+            [(1, 100), (2, 50), (1, 0)],
+            [(1, 100), (2, 50), (4, 0)],
+            true,
+        ),
+        // Sums do not overflow 64 bits
+        (
+            [(1, max), (1, max), (2, 1)],
+            [(1, max), (1, max), (2, 1)],
+            true,
+        ),
+        ([(1, max), (1, 1), (2, 0)], [(1, 0), (1, 0), (2, 0)], false),
+    ] {
+        let to_fr = |entries: [(u64, u64); 3]| {
+            entries
+                .iter()
+                .map(|(t, a)| (Some(BellmanFr::from(*t)), Some(BellmanFr::from(*a))))
+                .collect::<Vec<_>>()
+        };
+        let c = TestBalanceCircuit {
+            inputs: to_fr(inputs),
+            outputs: to_fr(outputs),
+        };
+        let proof = groth16::create_random_proof(c, &params, &mut OsRng).unwrap();
+        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+    }
+}
@@ -4,22 +4,13 @@ use crate::BellmanFr;
 use bellman::gadgets::boolean::Boolean;
 use bellman::{ConstraintSystem, SynthesisError};
 
+#[deprecated(note = "use `Number::from(b.clone())`")]
 pub fn extract_bool<CS: ConstraintSystem<BellmanFr>>(b: &Boolean) -> Number {
-    match b.clone() {
-        Boolean::Is(b) => b.into(),
-        Boolean::Not(not_b) => Number::one::<CS>() - not_b.into(),
-        Boolean::Constant(b_val) => {
-            if b_val {
-                Number::one::<CS>()
-            } else {
-                Number::zero()
-            }
-        }
-    }
+    Number::from(b.clone())
 }
 
 pub fn assert_true<CS: ConstraintSystem<BellmanFr>>(cs: &mut CS, b: &Boolean) {
-    extract_bool::<CS>(b).assert_equal(cs, &Number::one::<CS>());
+    Number::from(b.clone()).assert_equal(cs, &Number::from(BellmanFr::one()));
 }
 
 pub fn assert_true_if_enabled<CS: ConstraintSystem<BellmanFr>>(
@@ -27,7 +18,7 @@ pub fn assert_true_if_enabled<CS: ConstraintSystem<BellmanFr>>(
     enabled: &Boolean,
     cond: &Boolean,
 ) -> Result<(), SynthesisError> {
-    extract_bool::<CS>(cond).assert_equal_if_enabled(cs, enabled, &Number::one::<CS>())
+    Number::from(cond.clone()).assert_equal_if_enabled(cs, enabled, &Number::from(BellmanFr::one()))
 }
 
 pub fn boolean_or<CS: ConstraintSystem<BellmanFr>>(
@@ -37,3 +28,97 @@ pub fn boolean_or<CS: ConstraintSystem<BellmanFr>>(
 ) -> Result<Boolean, SynthesisError> {
     Ok(Boolean::and(&mut *cs, &a.not(), &b.not())?.not())
 }
+
+// Number of true bits as a linear combination, 0 constraints
+pub fn count_true(bits: &[Boolean]) -> Number {
+    bits.iter()
+        .fold(Number::zero(), |acc, b| acc + Number::from(b.clone()))
+}
+
+// The sum of the bits is nonzero, 2 constraints
+pub fn any_of<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    bits: &[Boolean],
+) -> Result<Boolean, SynthesisError> {
+    Ok(count_true(bits).is_zero(cs)?.not())
+}
+
+// The sum of the bits is n, 2 constraints
+pub fn all_of<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    bits: &[Boolean],
+) -> Result<Boolean, SynthesisError> {
+    let n = Number::from(BellmanFr::from(bits.len() as u64));
+    count_true(bits).is_equal(cs, &n)
+}
+
+// The sum of the bits is 1, 2 constraints
+pub fn exactly_one<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    bits: &[Boolean],
+) -> Result<Boolean, SynthesisError> {
+    count_true(bits).is_equal(cs, &Number::from(BellmanFr::one()))
+}
+
+// The sum of the bits is at least k, log2(n) + 3 constraints
+pub fn at_least_k_of_n<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    bits: &[Boolean],
+    k: usize,
+) -> Result<Boolean, SynthesisError> {
+    if k == 0 {
+        return Ok(Boolean::Constant(true));
+    }
+    if k > bits.len() {
+        return Ok(Boolean::Constant(false));
+    }
+
+    // Both count and k are at most n, so they fit in num_bits bits
+    let num_bits = (usize::BITS - bits.len().leading_zeros()) as usize;
+    let k = Number::from(BellmanFr::from(k as u64));
+    Ok(lt_numbers(cs, &count_true(bits), &k, num_bits)?.not())
+}
+
+// x is a root of the vanishing polynomial prod(x - c_i), n + 1 constraints
+pub fn is_in_set<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    x: &Number,
+    set: &[BellmanFr],
+) -> Result<Boolean, SynthesisError> {
+    if set.is_empty() {
+        return Ok(Boolean::Constant(false));
+    }
+    let mut prod = x.clone() - Number::from(set[0]);
+    for c in set[1..].iter() {
+        prod = prod.mul_number(&mut *cs, &(x.clone() - Number::from(*c)))?;
+    }
+    prod.is_zero(cs)
+}
+
+// Same as `assert_true(is_in_set(...))`, but the last factor is multiplied
+// straight into a zero, n - 1 constraints
+pub fn assert_in_set<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    x: &Number,
+    set: &[BellmanFr],
+) -> Result<(), SynthesisError> {
+    let (last, rest) = match set.split_last() {
+        Some(split) => split,
+        None => {
+            assert_true(cs, &Boolean::Constant(false));
+            return Ok(());
+        }
+    };
+    let mut prod = Number::from(BellmanFr::one());
+    for c in rest.iter() {
+        prod = prod.mul_number(&mut *cs, &(x.clone() - Number::from(*c)))?;
+    }
+    let last = x.clone() - Number::from(*last);
+    cs.enforce(
+        || "prod(x - c_i) == 0",
+        |lc| lc + prod.get_lc(),
+        |lc| lc + last.get_lc(),
+        |lc| lc,
+    );
+    Ok(())
+}
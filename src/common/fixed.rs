@@ -0,0 +1,268 @@
+use super::*;
+use crate::BellmanFr;
+use bellman::gadgets::boolean::Boolean;
+use bellman::{ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    // Ties are rounded up
+    Nearest,
+}
+
+// Native counterpart of `FixedPoint`, the value is raw / 2^FRAC_BITS. Every operation
+// rounds exactly like the circuit does, and returns None where the circuit would be
+// unsatisfiable (overflow, underflow and division by zero).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NativeFixedPoint<const FRAC_BITS: usize> {
+    raw: u128,
+    num_bits: usize,
+}
+
+fn fits(raw: u128, num_bits: usize) -> bool {
+    num_bits >= 128 || raw >> num_bits == 0
+}
+
+fn round(q: u128, round_up: bool, num_bits: usize) -> Option<u128> {
+    let q = q.checked_add(round_up as u128)?;
+    fits(q, num_bits).then_some(q)
+}
+
+impl<const FRAC_BITS: usize> NativeFixedPoint<FRAC_BITS> {
+    pub fn from_raw(raw: u128, num_bits: usize) -> Option<Self> {
+        fits(raw, num_bits).then_some(Self { raw, num_bits })
+    }
+    pub fn from_integer(val: u64, num_bits: usize) -> Option<Self> {
+        let raw = (val as u128).checked_shl(FRAC_BITS as u32)?;
+        if raw >> FRAC_BITS != val as u128 {
+            return None;
+        }
+        Self::from_raw(raw, num_bits)
+    }
+    pub fn raw(&self) -> u128 {
+        self.raw
+    }
+    pub fn num_bits(&self) -> usize {
+        self.num_bits
+    }
+    pub fn add(&self, other: &Self) -> Option<Self> {
+        assert_eq!(self.num_bits, other.num_bits);
+        Self::from_raw(self.raw.checked_add(other.raw)?, self.num_bits)
+    }
+    pub fn sub(&self, other: &Self) -> Option<Self> {
+        assert_eq!(self.num_bits, other.num_bits);
+        Self::from_raw(self.raw.checked_sub(other.raw)?, self.num_bits)
+    }
+    pub fn mul(&self, other: &Self, rounding: Rounding) -> Option<Self> {
+        assert_eq!(self.num_bits, other.num_bits);
+        assert!(FRAC_BITS > 0 && self.num_bits <= 64);
+        assert!(FRAC_BITS <= self.num_bits);
+        let prod = self.raw * other.raw;
+        let (q, r) = (prod >> FRAC_BITS, prod & ((1 << FRAC_BITS) - 1));
+        let round_up = match rounding {
+            Rounding::Floor => false,
+            Rounding::Ceil => r != 0,
+            Rounding::Nearest => r >> (FRAC_BITS - 1) == 1,
+        };
+        Self::from_raw(round(q, round_up, self.num_bits)?, self.num_bits)
+    }
+    pub fn div(&self, other: &Self, rounding: Rounding) -> Option<Self> {
+        assert_eq!(self.num_bits, other.num_bits);
+        assert!(FRAC_BITS <= self.num_bits && self.num_bits + FRAC_BITS <= 128);
+        if other.raw == 0 {
+            return None;
+        }
+        let numerator = self.raw << FRAC_BITS;
+        let (q, r) = (numerator / other.raw, numerator % other.raw);
+        let round_up = match rounding {
+            Rounding::Floor => false,
+            Rounding::Ceil => r != 0,
+            Rounding::Nearest => r >= other.raw - r,
+        };
+        Self::from_raw(round(q, round_up, self.num_bits)?, self.num_bits)
+    }
+}
+
+// Unsigned fixed-point number, the value is raw / 2^FRAC_BITS
+#[derive(Clone)]
+pub struct FixedPoint<const FRAC_BITS: usize> {
+    raw: UnsignedInteger,
+}
+
+impl<const FRAC_BITS: usize> FixedPoint<FRAC_BITS> {
+    pub fn from_raw(raw: UnsignedInteger) -> Self {
+        Self { raw }
+    }
+    pub fn raw(&self) -> &UnsignedInteger {
+        &self.raw
+    }
+    pub fn num_bits(&self) -> usize {
+        self.raw.num_bits()
+    }
+    pub fn get_value(&self) -> Option<NativeFixedPoint<FRAC_BITS>> {
+        self.raw
+            .get_value()
+            .and_then(|v| NativeFixedPoint::from_raw(to_u128(v), self.num_bits()))
+    }
+
+    // num_bits constraints
+    pub fn alloc<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        val: NativeFixedPoint<FRAC_BITS>,
+    ) -> Result<Self, SynthesisError> {
+        let raw = UnsignedInteger::alloc(cs, u128_to_fr(val.raw).into(), val.num_bits)?;
+        Ok(Self { raw })
+    }
+
+    // `num` is the raw value, num_bits + 1 constraints
+    pub fn constrain<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        num: Number,
+        num_bits: usize,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            raw: UnsignedInteger::constrain(cs, num, num_bits)?,
+        })
+    }
+
+    // Unsatisfiable on overflow, num_bits + 1 constraints
+    pub fn add<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &Self,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            raw: self.raw.add(cs, &other.raw)?,
+        })
+    }
+
+    // Unsatisfiable on underflow, num_bits + 1 constraints
+    pub fn sub<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &Self,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            raw: self.raw.sub(cs, &other.raw)?,
+        })
+    }
+
+    // Adds the rounding bit to a truncated result, unsatisfiable if it overflows
+    fn round<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        truncated: UnsignedInteger,
+        round_up: Option<Boolean>,
+    ) -> Result<Self, SynthesisError> {
+        let raw = match round_up {
+            Some(round_up) => {
+                let num_bits = truncated.num_bits();
+                let rounded = truncated.get_number().clone() + Number::from(round_up);
+                UnsignedInteger::constrain(cs, rounded, num_bits)?
+            }
+            None => truncated,
+        };
+        Ok(Self { raw })
+    }
+
+    // The 2 * num_bits product is split into a num_bits quotient and a FRAC_BITS
+    // remainder with q * 2^FRAC_BITS + r == a * b, so the truncation is proven
+    // rather than trusted. FRAC_BITS <= num_bits keeps q * 2^FRAC_BITS + r below
+    // 2^(2 * num_bits), so it can not wrap around the field. Unsatisfiable on overflow.
+    // Floor: num_bits + FRAC_BITS + 2 constraints, Ceil: num_bits + 3 more,
+    // Nearest: num_bits + 1 more
+    pub fn mul<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &Self,
+        rounding: Rounding,
+    ) -> Result<Self, SynthesisError> {
+        assert_eq!(self.num_bits(), other.num_bits());
+        assert!(FRAC_BITS > 0 && self.num_bits() <= 64);
+        assert!(FRAC_BITS <= self.num_bits());
+        assert!(2 * self.num_bits() < BellmanFr::CAPACITY as usize);
+        let num_bits = self.num_bits();
+
+        let prod = self
+            .raw
+            .get_number()
+            .mul_number(&mut *cs, other.raw.get_number())?;
+        let prod_val = prod.get_value().map(to_u128);
+        let (q_bits, q) = UnsignedInteger::alloc_bits(
+            &mut *cs,
+            prod_val.map(|p| u128_to_fr(p >> FRAC_BITS)),
+            num_bits,
+        )?;
+        let (r_bits, r) = UnsignedInteger::alloc_bits(
+            &mut *cs,
+            prod_val.map(|p| u128_to_fr(p & ((1 << FRAC_BITS) - 1))),
+            FRAC_BITS,
+        )?;
+        let shift = pow2(FRAC_BITS);
+        cs.enforce(
+            || "q * 2^FRAC_BITS + r == a * b",
+            |lc| lc + (shift, q.get_lc()) + r.get_lc(),
+            |lc| lc + CS::one(),
+            |lc| lc + prod.get_lc(),
+        );
+
+        let round_up = match rounding {
+            Rounding::Floor => None,
+            Rounding::Ceil => Some(r.is_zero(&mut *cs)?.not()),
+            Rounding::Nearest => Some(Boolean::Is(r_bits[FRAC_BITS - 1].clone())),
+        };
+        Self::round(cs, UnsignedInteger::from_bits(q_bits), round_up)
+    }
+
+    // Proves q * b + r == a * 2^FRAC_BITS with r < b. Division by zero and a
+    // quotient that does not fit in num_bits are unsatisfiable.
+    // Floor: 3 * num_bits + 4 constraints, Ceil: num_bits + 3 more,
+    // Nearest: 3 * num_bits + 5 more
+    pub fn div<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &Self,
+        rounding: Rounding,
+    ) -> Result<Self, SynthesisError> {
+        assert_eq!(self.num_bits(), other.num_bits());
+        assert!(FRAC_BITS <= self.num_bits() && self.num_bits() + FRAC_BITS <= 128);
+        assert!(2 * self.num_bits() + FRAC_BITS < BellmanFr::CAPACITY as usize);
+        let num_bits = self.num_bits();
+
+        let (q_val, r_val) = match self.raw.get_value().zip(other.raw.get_value()) {
+            Some((n, d)) => {
+                let (n, d) = (to_u128(n) << FRAC_BITS, to_u128(d));
+                if d == 0 {
+                    return Err(SynthesisError::DivisionByZero);
+                }
+                (Some(n / d), Some(n % d))
+            }
+            None => (None, None),
+        };
+        let (q_bits, q) = UnsignedInteger::alloc_bits(&mut *cs, q_val.map(u128_to_fr), num_bits)?;
+        let (r_bits, _) = UnsignedInteger::alloc_bits(&mut *cs, r_val.map(u128_to_fr), num_bits)?;
+        let remainder = UnsignedInteger::from_bits(r_bits);
+
+        let shift = pow2(FRAC_BITS);
+        cs.enforce(
+            || "q * b == a * 2^FRAC_BITS - r",
+            |lc| lc + q.get_lc(),
+            |lc| lc + other.raw.get_lc(),
+            |lc| lc + (shift, self.raw.get_lc()) - remainder.get_lc(),
+        );
+        let is_lt = remainder.lt(&mut *cs, &other.raw)?;
+        assert_true(&mut *cs, &is_lt);
+
+        let round_up = match rounding {
+            Rounding::Floor => None,
+            Rounding::Ceil => Some(remainder.get_number().is_zero(&mut *cs)?.not()),
+            Rounding::Nearest => {
+                // r >= b - r, never underflows since r < b
+                let rest = other.raw.sub(&mut *cs, &remainder)?;
+                Some(remainder.gte(&mut *cs, &rest)?)
+            }
+        };
+        Self::round(cs, UnsignedInteger::from_bits(q_bits), round_up)
+    }
+}
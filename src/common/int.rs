@@ -0,0 +1,212 @@
+use super::*;
+use crate::BellmanFr;
+use bazuka::zk::ZkScalar;
+use bellman::gadgets::boolean::{AllocatedBit, Boolean};
+use bellman::{ConstraintSystem, LinearCombination, SynthesisError};
+use ff::PrimeFieldBits;
+
+// Two's complement integer. The number is the signed value itself, a negative
+// value -v is represented as p - v in the field, so it can be mixed with other
+// numbers in field arithmetic directly.
+#[derive(Clone)]
+pub struct SignedInteger {
+    bits: Vec<AllocatedBit>,
+    num: Number,
+}
+
+impl SignedInteger {
+    pub fn get_lc(&self) -> &LinearCombination<BellmanFr> {
+        self.num.get_lc()
+    }
+    pub fn get_number(&self) -> &Number {
+        &self.num
+    }
+    pub fn get_value(&self) -> Option<BellmanFr> {
+        self.num.get_value()
+    }
+    // Two's complement bits, little-endian
+    pub fn bits(&self) -> &Vec<AllocatedBit> {
+        &self.bits
+    }
+    pub fn num_bits(&self) -> usize {
+        self.bits.len()
+    }
+    // The sign bit, 0 constraints
+    pub fn is_negative(&self) -> Boolean {
+        Boolean::Is(self.bits[self.num_bits() - 1].clone())
+    }
+
+    // sum(bits[i] * 2^i) - 2^num_bits * sign
+    fn from_twos_complement(bits: Vec<AllocatedBit>, packed: Number) -> Self {
+        let sign = Number::from(bits[bits.len() - 1].clone());
+        let num = packed + (-pow2(bits.len()), sign);
+        Self { bits, num }
+    }
+
+    // Reinterprets the bits of an unsigned integer as two's complement, 0 constraints
+    pub fn from_unsigned(u: &UnsignedInteger) -> Self {
+        assert!(u.num_bits() > 0);
+        Self::from_twos_complement(u.bits().clone(), u.get_number().clone())
+    }
+
+    // Reinterprets the two's complement bits as an unsigned integer, 0 constraints
+    pub fn to_unsigned(&self) -> UnsignedInteger {
+        UnsignedInteger::from_bits(self.bits.clone())
+    }
+
+    // `val` is a field element, negative values are given as p - |val|
+    // Unsatisfiable if val is not in [-2^(bits-1), 2^(bits-1)), num_bits constraints
+    pub fn alloc<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        val: ZkScalar,
+        bits: usize,
+    ) -> Result<Self, SynthesisError> {
+        assert!(bits > 0);
+        let val: BellmanFr = val.into();
+        let offset = val + pow2(bits - 1);
+        if offset.to_le_bits().iter().skip(bits).any(|b| *b) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let (bits, packed) =
+            UnsignedInteger::alloc_bits(cs, Some(to_twos_complement(val, bits)), bits)?;
+        Ok(Self::from_twos_complement(bits, packed))
+    }
+    pub fn alloc_32<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        val: i32,
+    ) -> Result<Self, SynthesisError> {
+        Self::alloc(cs, i64_to_scalar(val as i64), 32)
+    }
+    pub fn alloc_64<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        val: i64,
+    ) -> Result<Self, SynthesisError> {
+        Self::alloc(cs, i64_to_scalar(val), 64)
+    }
+
+    // Proves that num is in [-2^(num_bits-1), 2^(num_bits-1)), num_bits + 1 constraints
+    pub fn constrain<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        num: Number,
+        num_bits: usize,
+    ) -> Result<Self, SynthesisError> {
+        assert!(num_bits > 0);
+        let twos = num.get_value().map(|v| to_twos_complement(v, num_bits));
+        let (bits, packed) = UnsignedInteger::alloc_bits(&mut *cs, twos, num_bits)?;
+        let signed = Self::from_twos_complement(bits, packed);
+        cs.enforce(
+            || "check",
+            |lc| lc + signed.get_lc(),
+            |lc| lc + CS::one(),
+            |lc| lc + num.get_lc(),
+        );
+        Ok(Self {
+            bits: signed.bits,
+            num,
+        })
+    }
+
+    // Unsatisfiable for -2^(num_bits-1), num_bits + 1 constraints
+    pub fn neg<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<SignedInteger, SynthesisError> {
+        Self::constrain(cs, Number::zero() - self.num.clone(), self.num_bits())
+    }
+
+    // Never overflows, since |-2^(num_bits-1)| fits in num_bits unsigned bits,
+    // num_bits + 2 constraints
+    pub fn abs<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        let neg = Number::zero() - self.num.clone();
+        let abs = mux_number(&mut *cs, &self.is_negative(), &self.num, &neg)?;
+        UnsignedInteger::constrain(&mut *cs, abs, self.num_bits())
+    }
+
+    // Unsatisfiable on overflow, num_bits + 1 constraints
+    pub fn add<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &SignedInteger,
+    ) -> Result<SignedInteger, SynthesisError> {
+        assert_eq!(self.num_bits(), other.num_bits());
+        let sum = self.num.clone() + other.num.clone();
+        Self::constrain(cs, sum, self.num_bits())
+    }
+
+    // Unsatisfiable on overflow, num_bits + 1 constraints
+    pub fn sub<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &SignedInteger,
+    ) -> Result<SignedInteger, SynthesisError> {
+        assert_eq!(self.num_bits(), other.num_bits());
+        let sub = self.num.clone() - other.num.clone();
+        Self::constrain(cs, sub, self.num_bits())
+    }
+
+    // Operands of different widths are compared at the larger width,
+    // max(num_bits) + 3 constraints
+    pub fn lt<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &SignedInteger,
+    ) -> Result<Boolean, SynthesisError> {
+        let num_bits = std::cmp::max(self.num_bits(), other.num_bits());
+
+        // Both sides offset by 2^(num_bits - 1) are in [0, 2^num_bits)
+        let offset = Number::from(pow2(num_bits - 1));
+        lt_numbers(
+            cs,
+            &(self.num.clone() + offset.clone()),
+            &(other.num.clone() + offset),
+            num_bits,
+        )
+    }
+
+    pub fn gt<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &SignedInteger,
+    ) -> Result<Boolean, SynthesisError> {
+        other.lt(cs, self)
+    }
+
+    pub fn lte<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &SignedInteger,
+    ) -> Result<Boolean, SynthesisError> {
+        Ok(self.gt(cs, other)?.not())
+    }
+
+    pub fn gte<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &SignedInteger,
+    ) -> Result<Boolean, SynthesisError> {
+        Ok(self.lt(cs, other)?.not())
+    }
+}
+
+// v mod 2^num_bits, assuming v is in [-2^(num_bits-1), 2^(num_bits-1))
+fn to_twos_complement(v: BellmanFr, num_bits: usize) -> BellmanFr {
+    let half = pow2(num_bits - 1);
+    let offset = v + half;
+    if offset.to_le_bits()[num_bits - 1] {
+        offset - half
+    } else {
+        offset + half
+    }
+}
+
+fn i64_to_scalar(v: i64) -> ZkScalar {
+    let abs = BellmanFr::from(v.unsigned_abs());
+    if v < 0 {
+        (-abs).into()
+    } else {
+        abs.into()
+    }
+}
@@ -1,11 +1,21 @@
 mod boolean;
+mod fixed;
+mod int;
 mod mux;
 mod number;
+mod permutation;
 mod uint;
+mod uint8;
+mod wide;
 pub use boolean::*;
+pub use fixed::*;
+pub use int::*;
 pub use mux::*;
 pub use number::*;
+pub use permutation::*;
 pub use uint::*;
+pub use uint8::*;
+pub use wide::*;
 
 #[cfg(test)]
 mod test;
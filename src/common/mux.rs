@@ -1,9 +1,13 @@
 use super::*;
 use crate::BellmanFr;
-use bellman::gadgets::boolean::Boolean;
+use bellman::gadgets::boolean::{AllocatedBit, Boolean};
 use bellman::gadgets::num::AllocatedNum;
 use bellman::{ConstraintSystem, SynthesisError};
+use ff::Field;
 
+// Returns b if select is true, otherwise a, as a fresh variable, 1 constraint. This is
+// also the cost for a constant select, since the chosen option has to be allocated,
+// use `mux_number` to get it with no constraints
 pub fn mux<CS: ConstraintSystem<BellmanFr>>(
     cs: &mut CS,
     select: &Boolean,
@@ -40,8 +44,186 @@ pub fn mux<CS: ConstraintSystem<BellmanFr>>(
             );
             ret
         }
-        Boolean::Constant(_) => {
-            unimplemented!();
+        Boolean::Constant(s) => {
+            if *s {
+                b.compress(&mut *cs)?
+            } else {
+                a.compress(&mut *cs)?
+            }
         }
     })
 }
+
+// Same as `mux`, but returns the chosen option itself when select is a constant,
+// returns `a` when both options are the same linear combination, and folds into a
+// linear combination when both options are constants: a + (b - a) * select
+// (0 constraints in all of these cases)
+pub fn mux_number<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    select: &Boolean,
+    a: &Number,
+    b: &Number,
+) -> Result<Number, SynthesisError> {
+    if let Boolean::Constant(s) = select {
+        return Ok(if *s { b.clone() } else { a.clone() });
+    }
+    if same_lc(a, b) {
+        return Ok(a.clone());
+    }
+    if let (Some(a_val), Some(b_val)) = (a.get_constant(), b.get_constant()) {
+        return Ok(Number::from(a_val) + (b_val - a_val, Number::from(select.clone())));
+    }
+    Ok(mux(cs, select, a, b)?.into())
+}
+
+// Element-wise `mux_number`, returns b if select is true, 1 constraint per element
+pub fn mux_vec<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    select: &Boolean,
+    a: &[Number],
+    b: &[Number],
+) -> Result<Vec<Number>, SynthesisError> {
+    assert_eq!(a.len(), b.len());
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| mux_number(&mut *cs, select, a, b))
+        .collect()
+}
+
+// Returns (b, a) if cond is true, otherwise (a, b). Both outputs share a single
+// t = cond * (b - a) term, 1 constraint (0 if cond is constant)
+pub fn cswap<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    cond: &Boolean,
+    a: &Number,
+    b: &Number,
+) -> Result<(Number, Number), SynthesisError> {
+    let t = Number::from(cond.clone()).mul_number(&mut *cs, &(b.clone() - a.clone()))?;
+    Ok((a.clone() + t.clone(), b.clone() - t))
+}
+
+fn same_lc(a: &Number, b: &Number) -> bool {
+    let (a, b) = (a.get_lc().as_ref(), b.get_lc().as_ref());
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|((a_var, a_coeff), (b_var, b_coeff))| {
+                a_var.get_unchecked() == b_var.get_unchecked() && a_coeff == b_coeff
+            })
+}
+
+// Selects options[index], where index is given by its little-endian bits. Costs
+// n - 1 constraints for n variable options. Constant options are looked up 4 at a
+// time, sharing a single b0 * b1 constraint, so 4 constant options cost 1 constraint
+// and 8 constant options cost 2.
+pub fn mux_n<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    index_bits: &[Boolean],
+    options: &[Number],
+) -> Result<Number, SynthesisError> {
+    assert_eq!(options.len(), 1 << index_bits.len());
+    let mut options = options.to_vec();
+    let mut bits = index_bits;
+    if bits.len() >= 2 && options.iter().all(|o| o.is_constant()) {
+        let b0 = Number::from(bits[0].clone());
+        let b1 = Number::from(bits[1].clone());
+        let b0b1 = Number::from(Boolean::and(&mut *cs, &bits[0], &bits[1])?);
+        options = options
+            .chunks(4)
+            .map(|c| {
+                let c: Vec<BellmanFr> = c.iter().map(|o| o.get_constant().unwrap()).collect();
+                Number::from(c[0])
+                    + (c[1] - c[0], b0.clone())
+                    + (c[2] - c[0], b1.clone())
+                    + (c[3] - c[2] - c[1] + c[0], b0b1.clone())
+            })
+            .collect();
+        bits = &bits[2..];
+    }
+    for bit in bits {
+        options = options
+            .chunks(2)
+            .map(|pair| mux_number(&mut *cs, bit, &pair[0], &pair[1]))
+            .collect::<Result<Vec<_>, _>>()?;
+    }
+    Ok(options.remove(0))
+}
+
+// Dynamic array read, out of range indices read as zero. Only the lowest
+// ceil(log2(options.len())) bits of the index go through `mux_n`, the rest cost
+// 3 constraints for the range check.
+pub fn select_by_index<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    index: &UnsignedInteger,
+    options: &[Number],
+) -> Result<Number, SynthesisError> {
+    assert!(!options.is_empty());
+    let num_bits = options.len().next_power_of_two().trailing_zeros() as usize;
+    let mut options = options.to_vec();
+    if num_bits > index.num_bits() {
+        options.truncate(1 << index.num_bits());
+    }
+    let bits: Vec<Boolean> = index
+        .bits()
+        .iter()
+        .take(num_bits)
+        .map(|b| Boolean::Is(b.clone()))
+        .collect();
+    options.resize(1 << bits.len(), Number::zero());
+    let selected = mux_n(&mut *cs, &bits, &options)?;
+
+    if index.num_bits() > bits.len() {
+        let high_bits = index.bits()[bits.len()..]
+            .iter()
+            .fold(Number::zero(), |acc, b| acc + Number::from(b.clone()));
+        let in_range = Number::from(high_bits.is_zero(&mut *cs)?);
+        selected.mul_number(&mut *cs, &in_range)
+    } else {
+        Ok(selected)
+    }
+}
+
+// Decomposes index into a one-hot vector of the given length, each entry proven
+// with `is_equal`, 2 constraints per entry. All entries are false when the index
+// is out of range.
+pub fn one_hot<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    index: &UnsignedInteger,
+    len: usize,
+) -> Result<Vec<Boolean>, SynthesisError> {
+    (0..len)
+        .map(|i| {
+            index
+                .get_number()
+                .is_equal(&mut *cs, &Number::from(BellmanFr::from(i as u64)))
+        })
+        .collect()
+}
+
+// Dynamic array write (array[index] = value). Returns the updated array and an
+// out-of-bounds flag, the array is left untouched when the index is out of range.
+// 3 constraints per element, plus 2 for the flag.
+pub fn write_by_index<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    array: &[Number],
+    index: &UnsignedInteger,
+    value: &Number,
+) -> Result<(Vec<Number>, Boolean), SynthesisError> {
+    let selectors = one_hot(&mut *cs, index, array.len())?;
+    let hits = selectors
+        .iter()
+        .fold(Number::zero(), |acc, s| acc + Number::from(s.clone()));
+
+    // At most one selector is set, so out_of_bounds == 1 - sum(selectors)
+    let out_of_bounds =
+        AllocatedBit::alloc(&mut *cs, hits.get_value().map(|h| h.is_zero().into()))?;
+    (hits + Number::from(out_of_bounds.clone()))
+        .assert_equal(&mut *cs, &Number::from(BellmanFr::one()));
+
+    let updated = array
+        .iter()
+        .zip(selectors.iter())
+        .map(|(elem, s)| mux_number(&mut *cs, s, elem, value))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((updated, Boolean::Is(out_of_bounds)))
+}
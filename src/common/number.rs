@@ -2,7 +2,7 @@ use super::*;
 use crate::BellmanFr;
 use bellman::gadgets::boolean::{AllocatedBit, Boolean};
 use bellman::gadgets::num::AllocatedNum;
-use bellman::{ConstraintSystem, LinearCombination, SynthesisError};
+use bellman::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
 use ff::Field;
 use std::ops::*;
 
@@ -15,9 +15,9 @@ impl Number {
     pub fn get_value(&self) -> Option<BellmanFr> {
         self.1
     }
+    #[deprecated(note = "use `*self = self.clone() + Number::from(num)`")]
     pub fn add_constant<CS: ConstraintSystem<BellmanFr>>(&mut self, num: BellmanFr) {
-        self.0 = self.0.clone() + (num, CS::one());
-        self.1 = self.1.map(|v| v + num);
+        *self = self.clone() + Number::from(num);
     }
     pub fn add_num(&mut self, coeff: BellmanFr, num: &AllocatedNum<BellmanFr>) {
         self.0 = self.0.clone() + (coeff, num.get_variable());
@@ -27,11 +27,9 @@ impl Number {
             None
         };
     }
+    #[deprecated(note = "use `Number::from(v)`")]
     pub fn constant<CS: ConstraintSystem<BellmanFr>>(v: BellmanFr) -> Number {
-        Number(
-            LinearCombination::<BellmanFr>::zero() + (v, CS::one()),
-            Some(v),
-        )
+        Number::from(v)
     }
     pub fn zero() -> Number {
         Number(
@@ -39,12 +37,29 @@ impl Number {
             Some(BellmanFr::zero()),
         )
     }
+    #[deprecated(note = "use `Number::from(BellmanFr::one())`")]
     pub fn one<CS: ConstraintSystem<BellmanFr>>() -> Number {
-        Number(
-            LinearCombination::<BellmanFr>::zero() + CS::one(),
-            Some(BellmanFr::one()),
-        )
+        Number::from(BellmanFr::one())
+    }
+    // Some(v) if the number does not depend on any variable other than the one
+    // variable, i.e. its value is known at circuit synthesis time
+    pub fn get_constant(&self) -> Option<BellmanFr> {
+        let mut sum = BellmanFr::zero();
+        for (var, coeff) in self.0.as_ref() {
+            if coeff.is_zero_vartime() {
+                continue;
+            }
+            if var.get_unchecked() != one_variable().get_unchecked() {
+                return None;
+            }
+            sum += coeff;
+        }
+        Some(sum)
+    }
+    pub fn is_constant(&self) -> bool {
+        self.get_constant().is_some()
     }
+
     pub fn mul<CS: ConstraintSystem<BellmanFr>>(
         &self,
         cs: &mut CS,
@@ -64,18 +79,45 @@ impl Number {
         );
         Ok(result)
     }
+    // Same as `mul`, but multiplying by a constant is linear, so there are 0 constraints
+    // if any side is constant, otherwise 1 constraint
+    pub fn mul_number<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &Number,
+    ) -> Result<Number, SynthesisError> {
+        if let Some(c) = self.get_constant() {
+            return Ok(Number::zero() + (c, other.clone()));
+        }
+        if let Some(c) = other.get_constant() {
+            return Ok(Number::zero() + (c, self.clone()));
+        }
+        Ok(self.mul(cs, other)?.into())
+    }
     pub fn compress<CS: ConstraintSystem<BellmanFr>>(
         &self,
         cs: &mut CS,
     ) -> Result<AllocatedNum<BellmanFr>, SynthesisError> {
-        self.mul::<CS>(cs, &Self::one::<CS>())
+        let result = AllocatedNum::alloc(&mut *cs, || {
+            self.get_value().ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce(
+            || "",
+            |lc| lc + self.get_lc(),
+            |lc| lc + CS::one(),
+            |lc| lc + result.get_variable(),
+        );
+        Ok(result)
     }
 
-    // Check if a number is zero, 2 constraints
+    // Check if a number is zero, 2 constraints (0 constraints if constant)
     pub fn is_zero<CS: ConstraintSystem<BellmanFr>>(
         &self,
         cs: &mut CS,
     ) -> Result<Boolean, SynthesisError> {
+        if let Some(c) = self.get_constant() {
+            return Ok(Boolean::Constant(c.is_zero().into()));
+        }
         let is_zero =
             AllocatedBit::alloc(&mut *cs, self.get_value().map(|num| num.is_zero().into()))?;
         let inv = AllocatedNum::alloc(&mut *cs, || {
@@ -110,6 +152,32 @@ impl Number {
         Ok(Boolean::Is(is_zero))
     }
 
+    // Compare two arbitrary field elements by their canonical representatives.
+    // Both sides are strictly decomposed and each split into two halves that are
+    // small enough to be compared by `UnsignedInteger::lt`, 1044 constraints
+    pub fn lt_field<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &Number,
+    ) -> Result<Boolean, SynthesisError> {
+        let self_bits = UnsignedInteger::constrain_strict(&mut *cs, self.clone())?;
+        let other_bits = UnsignedInteger::constrain_strict(&mut *cs, other.clone())?;
+        let half = self_bits.num_bits() / 2;
+        let rest = self_bits.num_bits() - half;
+        let (self_lo, self_hi) = (self_bits.slice(0, half)?, self_bits.slice(half, rest)?);
+        let (other_lo, other_hi) = (other_bits.slice(0, half)?, other_bits.slice(half, rest)?);
+
+        let hi_lt = self_hi.lt(&mut *cs, &other_hi)?;
+        let hi_eq = self_hi
+            .get_number()
+            .is_equal(&mut *cs, other_hi.get_number())?;
+        let lo_lt = self_lo.lt(&mut *cs, &other_lo)?;
+
+        // self < other <=> hi_lt || (hi_eq && lo_lt)
+        let hi_eq_and_lo_lt = Boolean::and(&mut *cs, &hi_eq, &lo_lt)?;
+        boolean_or(&mut *cs, &hi_lt, &hi_eq_and_lo_lt)
+    }
+
     pub fn is_equal<CS: ConstraintSystem<BellmanFr>>(
         &self,
         cs: &mut CS,
@@ -127,56 +195,38 @@ impl Number {
         );
     }
 
+    // Assert equality only if enabled, any kind of Boolean is accepted, 1 constraint
+    // (0 constraints if enabled is constant false)
     pub fn assert_equal_if_enabled<CS: ConstraintSystem<BellmanFr>>(
         &self,
         cs: &mut CS,
         enabled: &Boolean,
         other: &Number,
     ) -> Result<(), SynthesisError> {
-        match enabled {
-            Boolean::Is(enabled) => {
-                let enabled_value = enabled.get_value();
-                let enabled_in_self = cs.alloc(
-                    || "",
-                    || {
-                        enabled_value
-                            .map(|e| {
-                                if e {
-                                    self.get_value()
-                                } else {
-                                    Some(BellmanFr::zero())
-                                }
-                            })
-                            .unwrap_or(None)
-                            .ok_or(SynthesisError::AssignmentMissing)
-                    },
-                )?;
-                cs.enforce(
-                    || "enabled * self == enabled_in_self",
-                    |lc| lc + enabled.get_variable(),
-                    |lc| lc + self.get_lc(),
-                    |lc| lc + enabled_in_self,
-                );
-                cs.enforce(
-                    || "enabled * other == enabled_in_self",
-                    |lc| lc + enabled.get_variable(),
-                    |lc| lc + other.get_lc(),
-                    |lc| lc + enabled_in_self,
-                );
-            }
-            Boolean::Not(_) => {
-                unimplemented!();
-            }
-            Boolean::Constant(enabled) => {
-                if *enabled {
-                    self.assert_equal(&mut *cs, other);
-                }
+        let enabled = Number::from(enabled.clone());
+        if let Some(c) = enabled.get_constant() {
+            if !c.is_zero_vartime() {
+                self.assert_equal(cs, other);
             }
+            return Ok(());
         }
+        cs.enforce(
+            || "enabled * (self - other) == 0",
+            |lc| lc + enabled.get_lc(),
+            |lc| lc + self.get_lc() - other.get_lc(),
+            |lc| lc,
+        );
         Ok(())
     }
 }
 
+// Bellman reserves the first input variable of every constraint system for the
+// constant one (this is what `ConstraintSystem::one()` returns), so constants can
+// be expressed without knowing the concrete constraint system type.
+fn one_variable() -> Variable {
+    Variable::new_unchecked(Index::Input(0))
+}
+
 impl Add for Number {
     type Output = Self;
 
@@ -212,6 +262,31 @@ impl Sub for Number {
     }
 }
 
+impl From<BellmanFr> for Number {
+    fn from(v: BellmanFr) -> Self {
+        Self(
+            LinearCombination::<BellmanFr>::zero() + (v, one_variable()),
+            Some(v),
+        )
+    }
+}
+
+impl From<Boolean> for Number {
+    fn from(b: Boolean) -> Self {
+        match b {
+            Boolean::Is(b) => b.into(),
+            Boolean::Not(not_b) => Number::from(BellmanFr::one()) - Number::from(not_b),
+            Boolean::Constant(b_val) => {
+                if b_val {
+                    Number::from(BellmanFr::one())
+                } else {
+                    Number::zero()
+                }
+            }
+        }
+    }
+}
+
 impl From<AllocatedNum<BellmanFr>> for Number {
     fn from(a: AllocatedNum<BellmanFr>) -> Self {
         Self(
@@ -248,3 +323,12 @@ impl From<UnsignedInteger> for Number {
         )
     }
 }
+
+impl From<SignedInteger> for Number {
+    fn from(a: SignedInteger) -> Self {
+        Self(
+            LinearCombination::<BellmanFr>::zero() + a.get_lc(),
+            a.get_value(),
+        )
+    }
+}
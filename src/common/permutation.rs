@@ -0,0 +1,96 @@
+use super::*;
+use crate::poseidon;
+use crate::BellmanFr;
+use bellman::{ConstraintSystem, SynthesisError};
+
+// Puts the smaller value first, num_bits + 4 constraints
+fn compare_and_swap<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    vals: &mut [Number],
+    i: usize,
+    j: usize,
+    num_bits: usize,
+) -> Result<(), SynthesisError> {
+    let swap = lt_numbers(&mut *cs, &vals[j], &vals[i], num_bits)?;
+    let (a, b) = cswap(&mut *cs, &swap, &vals[i], &vals[j])?;
+    vals[i] = a;
+    vals[j] = b;
+    Ok(())
+}
+
+// Sorts in ascending order with Batcher's odd-even merge sort network. Every
+// comparator costs num_bits + 4 constraints, and the outputs are decomposed into
+// bits again at the end (num_bits + 1 constraints each). The comparators can work
+// on plain numbers, since their inputs are always a permutation of the range
+// checked values.
+pub fn sort<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    values: &[UnsignedInteger],
+) -> Result<Vec<UnsignedInteger>, SynthesisError> {
+    let num_bits = values.iter().map(|v| v.num_bits()).max().unwrap_or(0);
+    let mut vals: Vec<Number> = values.iter().map(|v| v.get_number().clone()).collect();
+    let n = vals.len();
+
+    let mut p = 1;
+    while p < n {
+        let mut k = p;
+        while k > 0 {
+            let mut j = k % p;
+            while j + k < n {
+                for i in 0..std::cmp::min(k, n - j - k) {
+                    if (i + j) / (p * 2) == (i + j + k) / (p * 2) {
+                        compare_and_swap(&mut *cs, &mut vals, i + j, i + j + k, num_bits)?;
+                    }
+                }
+                j += k * 2;
+            }
+            k /= 2;
+        }
+        p *= 2;
+    }
+
+    vals.into_iter()
+        .map(|v| UnsignedInteger::constrain(&mut *cs, v, num_bits))
+        .collect()
+}
+
+// Fiat-Shamir challenge, absorbing 3 values per poseidon call
+fn challenge<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    vals: &[Number],
+) -> Result<Number, SynthesisError> {
+    let mut acc = Number::zero();
+    for chunk in vals.chunks(3) {
+        let mut inputs = vec![&acc];
+        inputs.extend(chunk.iter());
+        acc = poseidon::poseidon(&mut *cs, &inputs)?;
+    }
+    Ok(acc)
+}
+
+// Proves that b is a permutation of a through prod(r - a[i]) == prod(r - b[i]),
+// where the challenge r is the hash of both lists, so it can not be chosen after
+// the lists. 2 * (n - 1) + 1 constraints plus one poseidon per 3 elements.
+pub fn assert_permutation<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    a: &[Number],
+    b: &[Number],
+) -> Result<(), SynthesisError> {
+    assert_eq!(a.len(), b.len());
+    if a.is_empty() {
+        return Ok(());
+    }
+    let all: Vec<Number> = a.iter().chain(b.iter()).cloned().collect();
+    let r = challenge(&mut *cs, &all)?;
+
+    let mut prods = Vec::new();
+    for vals in [a, b] {
+        let mut prod = r.clone() - vals[0].clone();
+        for v in vals[1..].iter() {
+            prod = prod.mul_number(&mut *cs, &(r.clone() - v.clone()))?;
+        }
+        prods.push(prod);
+    }
+    prods[0].assert_equal(&mut *cs, &prods[1]);
+    Ok(())
+}
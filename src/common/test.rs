@@ -1,6 +1,8 @@
 use super::*;
+use crate::test_utils::CountingConstraintSystem;
 use crate::BellmanFr;
 use crate::Bls12;
+use bazuka::zk::ZkScalar;
 use bellman::gadgets::boolean::{AllocatedBit, Boolean};
 use bellman::gadgets::num::AllocatedNum;
 use bellman::{groth16, Circuit, ConstraintSystem, SynthesisError};
@@ -23,7 +25,7 @@ impl Circuit<BellmanFr> for TestIsEqualCircuit {
         let eq = AllocatedBit::alloc(&mut *cs, self.is_equal)?;
 
         let res_bool = Number::from(a).is_equal(&mut *cs, &b.into())?;
-        let res = extract_bool::<CS>(&res_bool);
+        let res = Number::from(res_bool.clone());
         cs.enforce(
             || "",
             |lc| lc + res.get_lc(),
@@ -84,7 +86,7 @@ impl Circuit<BellmanFr> for TestLteCircuit {
         let is_lte = AllocatedBit::alloc(&mut *cs, self.is_lte)?;
 
         let res_bool = a_64.lte(&mut *cs, &b_64)?;
-        let res = extract_bool::<CS>(&res_bool);
+        let res = Number::from(res_bool.clone());
         cs.enforce(
             || "",
             |lc| lc + res.get_lc(),
@@ -166,7 +168,7 @@ impl Circuit<BellmanFr> for TestOrCircuit {
         let b = Boolean::Is(AllocatedBit::alloc(&mut *cs, self.b)?);
         let expected = AllocatedBit::alloc(&mut *cs, self.or_result)?;
         let or = boolean_or(&mut *cs, &a, &b)?;
-        let or_num = extract_bool::<CS>(&or);
+        let or_num = Number::from(or.clone());
         or_num.assert_equal(&mut *cs, &expected.into());
 
         Ok(())
@@ -205,3 +207,2270 @@ fn test_or_circuit() {
         assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
     }
 }
+
+#[derive(Clone)]
+struct TestLtFieldCircuit {
+    a: Option<BellmanFr>,
+    b: Option<BellmanFr>,
+    is_lt: Option<bool>,
+}
+
+impl Circuit<BellmanFr> for TestLtFieldCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = AllocatedNum::alloc(&mut *cs, || self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let is_lt = AllocatedBit::alloc(&mut *cs, self.is_lt)?;
+
+        let res_bool = Number::from(a).lt_field(&mut *cs, &b.into())?;
+        let res = Number::from(res_bool.clone());
+        res.assert_equal(&mut *cs, &is_lt.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_lt_field_circuit() {
+    let params = {
+        let c = TestLtFieldCircuit {
+            a: None,
+            b: None,
+            is_lt: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    let two = BellmanFr::from(2);
+    let p_minus_1 = -BellmanFr::one();
+    let p_minus_2 = p_minus_1 - BellmanFr::one();
+    let big = two.pow_vartime(&[200, 0, 0, 0]);
+    let mid = two.pow_vartime(&[130, 0, 0, 0]);
+
+    for (a, b, lt, expected) in [
+        (BellmanFr::zero(), BellmanFr::zero(), false, true),
+        (BellmanFr::zero(), BellmanFr::zero(), true, false),
+        (BellmanFr::zero(), BellmanFr::one(), true, true),
+        (BellmanFr::one(), BellmanFr::zero(), false, true),
+        (BellmanFr::one(), BellmanFr::zero(), true, false),
+        (BellmanFr::zero(), p_minus_1, true, true),
+        (p_minus_1, BellmanFr::zero(), false, true),
+        (p_minus_1, BellmanFr::zero(), true, false),
+        (p_minus_2, p_minus_1, true, true),
+        (p_minus_1, p_minus_2, false, true),
+        (p_minus_1, p_minus_1, false, true),
+        (big, big + BellmanFr::one(), true, true),
+        (big + BellmanFr::one(), big, false, true),
+        (
+            mid + BellmanFr::from(5),
+            mid + BellmanFr::from(6),
+            true,
+            true,
+        ),
+        (
+            mid + BellmanFr::from(6),
+            mid + BellmanFr::from(5),
+            false,
+            true,
+        ),
+        (
+            mid + BellmanFr::from(6),
+            mid + BellmanFr::from(5),
+            true,
+            false,
+        ),
+        (mid, BellmanFr::from(u64::MAX), false, true),
+        (BellmanFr::from(u64::MAX), mid, true, true),
+    ] {
+        let c = TestLtFieldCircuit {
+            a: Some(a),
+            b: Some(b),
+            is_lt: Some(lt),
+        };
+        let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+    }
+}
+
+#[derive(Clone, Copy)]
+enum EnabledKind {
+    Is,
+    Not,
+    Constant(bool),
+}
+
+#[derive(Clone)]
+struct TestAssertEqualIfEnabledCircuit {
+    kind: EnabledKind,
+    enabled: Option<bool>,
+    a: Option<BellmanFr>,
+    b: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestAssertEqualIfEnabledCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = AllocatedNum::alloc(&mut *cs, || self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let enabled = match self.kind {
+            EnabledKind::Is => Boolean::Is(AllocatedBit::alloc(&mut *cs, self.enabled)?),
+            EnabledKind::Not => {
+                Boolean::Is(AllocatedBit::alloc(&mut *cs, self.enabled.map(|e| !e))?).not()
+            }
+            EnabledKind::Constant(c) => Boolean::Constant(c),
+        };
+
+        Number::from(a.clone()).assert_equal_if_enabled(&mut *cs, &enabled, &b.clone().into())?;
+
+        if let EnabledKind::Constant(false) = self.kind {
+            // The inputs are left unused otherwise
+            let all = Number::from(a) + Number::from(b);
+            all.assert_equal(&mut *cs, &all);
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_assert_equal_if_enabled_circuit() {
+    for kind in [
+        EnabledKind::Is,
+        EnabledKind::Not,
+        EnabledKind::Constant(true),
+        EnabledKind::Constant(false),
+    ] {
+        let params = {
+            let c = TestAssertEqualIfEnabledCircuit {
+                kind,
+                enabled: None,
+                a: None,
+                b: None,
+            };
+            groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+        };
+
+        let pvk = groth16::prepare_verifying_key(&params.vk);
+
+        for (enabled, a, b) in [
+            (true, 123, 123),
+            (true, 123, 234),
+            (false, 123, 123),
+            (false, 123, 234),
+        ] {
+            let enabled = if let EnabledKind::Constant(c) = kind {
+                c
+            } else {
+                enabled
+            };
+            let expected = !enabled || a == b;
+            let c = TestAssertEqualIfEnabledCircuit {
+                kind,
+                enabled: Some(enabled),
+                a: Some(BellmanFr::from(a)),
+                b: Some(BellmanFr::from(b)),
+            };
+            let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+            assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+        }
+    }
+}
+
+#[test]
+fn test_assert_equal_if_enabled_constant() {
+    let mut cs = bellman::gadgets::test::TestConstraintSystem::<BellmanFr>::new();
+    let a = AllocatedNum::alloc(&mut cs.namespace(|| "a"), || Ok(BellmanFr::from(123))).unwrap();
+    let b = AllocatedNum::alloc(&mut cs.namespace(|| "b"), || Ok(BellmanFr::from(234))).unwrap();
+    let num_constraints = cs.num_constraints();
+
+    Number::from(a.clone())
+        .assert_equal_if_enabled(&mut cs, &Boolean::Constant(false), &b.clone().into())
+        .unwrap();
+    assert_eq!(cs.num_constraints(), num_constraints);
+    assert!(cs.is_satisfied());
+
+    Number::from(a)
+        .assert_equal_if_enabled(&mut cs, &Boolean::Constant(true), &b.into())
+        .unwrap();
+    assert_eq!(cs.num_constraints(), num_constraints + 1);
+    assert!(!cs.is_satisfied());
+}
+
+#[derive(Clone)]
+struct TestAssertEqualIfOrCircuit {
+    a: Option<bool>,
+    b: Option<bool>,
+    x: Option<BellmanFr>,
+    y: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestAssertEqualIfOrCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = Boolean::Is(AllocatedBit::alloc(&mut *cs, self.a)?);
+        let b = Boolean::Is(AllocatedBit::alloc(&mut *cs, self.b)?);
+        let x = AllocatedNum::alloc(&mut *cs, || self.x.ok_or(SynthesisError::AssignmentMissing))?;
+        let y = AllocatedNum::alloc(&mut *cs, || self.y.ok_or(SynthesisError::AssignmentMissing))?;
+        let enabled = boolean_or(&mut *cs, &a, &b)?;
+        Number::from(x).assert_equal_if_enabled(&mut *cs, &enabled, &y.into())?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_assert_equal_if_or_circuit() {
+    let params = {
+        let c = TestAssertEqualIfOrCircuit {
+            a: None,
+            b: None,
+            x: None,
+            y: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for (a, b, x, y, expected) in [
+        (false, false, 123, 123, true),
+        (false, false, 123, 234, true),
+        (true, false, 123, 123, true),
+        (true, false, 123, 234, false),
+        (false, true, 123, 123, true),
+        (false, true, 123, 234, false),
+        (true, true, 123, 123, true),
+        (true, true, 123, 234, false),
+    ] {
+        let c = TestAssertEqualIfOrCircuit {
+            a: Some(a),
+            b: Some(b),
+            x: Some(BellmanFr::from(x)),
+            y: Some(BellmanFr::from(y)),
+        };
+        let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+    }
+}
+
+#[derive(Clone)]
+struct TestConstantsCircuit {
+    a: Option<BellmanFr>,
+    b: Option<bool>,
+    out: Option<BellmanFr>,
+}
+
+// Builds constants without naming the constraint system type
+fn constant_gadget(a: Number, b: Boolean) -> Number {
+    a + Number::from(BellmanFr::from(10)) - Number::from(BellmanFr::from(3)) + Number::from(b.not())
+        - Number::from(Boolean::Constant(true))
+        + (BellmanFr::from(2), Number::from(BellmanFr::from(5)))
+}
+
+impl Circuit<BellmanFr> for TestConstantsCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = Boolean::Is(AllocatedBit::alloc(&mut *cs, self.b)?);
+        let out = AllocatedNum::alloc(&mut *cs, || {
+            self.out.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let res = constant_gadget(a.into(), b) + Number::from(BellmanFr::one());
+        res.assert_equal(&mut *cs, &out.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_constants_circuit() {
+    let params = {
+        let c = TestConstantsCircuit {
+            a: None,
+            b: None,
+            out: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for (a, b, out, expected) in [
+        (100, false, 118, true),
+        (100, true, 117, true),
+        (100, false, 117, false),
+        (100, true, 118, false),
+    ] {
+        let c = TestConstantsCircuit {
+            a: Some(BellmanFr::from(a)),
+            b: Some(b),
+            out: Some(BellmanFr::from(out)),
+        };
+        let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+    }
+}
+
+#[test]
+fn test_constant_folding() {
+    let mut cs = bellman::gadgets::test::TestConstraintSystem::<BellmanFr>::new();
+    let a = Number::from(BellmanFr::from(6));
+    let b = Number::from(BellmanFr::from(7));
+    let s = Boolean::Is(AllocatedBit::alloc(&mut cs, Some(true)).unwrap());
+    let num_constraints = cs.num_constraints();
+
+    let prod = a.mul_number(&mut cs, &b).unwrap();
+    assert_eq!(prod.get_constant(), Some(BellmanFr::from(42)));
+    assert_eq!(prod.is_zero(&mut cs).unwrap().get_value(), Some(false));
+    assert_eq!(
+        Number::zero().is_zero(&mut cs).unwrap().get_value(),
+        Some(true)
+    );
+    let muxed = mux_number(&mut cs, &s, &a, &b).unwrap();
+    assert!(!muxed.is_constant());
+    assert_eq!(muxed.get_value(), Some(BellmanFr::from(7)));
+    let muxed = mux_number(&mut cs, &s.not(), &a, &b).unwrap();
+    assert_eq!(muxed.get_value(), Some(BellmanFr::from(6)));
+
+    // A constant selector picks the option itself, even if it is not constant
+    let var = Number::from(s.clone());
+    let muxed = mux_number(&mut cs, &Boolean::Constant(true), &a, &var).unwrap();
+    assert!(!muxed.is_constant());
+    assert_eq!(muxed.get_value(), Some(BellmanFr::one()));
+    let muxed = mux_number(&mut cs, &Boolean::Constant(false), &a, &var).unwrap();
+    assert_eq!(muxed.get_constant(), Some(BellmanFr::from(6)));
+
+    // Multiplying a variable by a constant is linear
+    let scaled = Number::from(s.clone()).mul_number(&mut cs, &b).unwrap();
+    assert_eq!(scaled.get_value(), Some(BellmanFr::from(7)));
+
+    assert_eq!(cs.num_constraints(), num_constraints);
+    assert!(cs.is_satisfied());
+}
+
+#[test]
+fn test_mux_constant_select() {
+    let mut cs = bellman::gadgets::test::TestConstraintSystem::<BellmanFr>::new();
+    let s = AllocatedBit::alloc(&mut cs, Some(true)).unwrap();
+    let a = Number::from(BellmanFr::from(6));
+    let b = Number::from(s);
+    let num_constraints = cs.num_constraints();
+
+    let muxed = mux(&mut cs, &Boolean::Constant(false), &a, &b).unwrap();
+    assert_eq!(muxed.get_value(), Some(BellmanFr::from(6)));
+    let muxed = mux(
+        &mut cs.namespace(|| "true"),
+        &Boolean::Constant(true),
+        &a,
+        &b,
+    )
+    .unwrap();
+    assert_eq!(muxed.get_value(), Some(BellmanFr::one()));
+
+    // The chosen option still has to be allocated, 1 constraint each
+    assert_eq!(cs.num_constraints(), num_constraints + 2);
+    assert!(cs.is_satisfied());
+}
+
+#[derive(Clone)]
+struct TestConstantFoldingCircuit {
+    s: Option<bool>,
+    out: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestConstantFoldingCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let s = Boolean::Is(AllocatedBit::alloc(&mut *cs, self.s)?);
+        let out = AllocatedNum::alloc(&mut *cs, || {
+            self.out.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let a = Number::from(BellmanFr::from(6));
+        let b = Number::from(BellmanFr::from(7));
+        let prod = a.mul_number(&mut *cs, &b)?;
+        let muxed = mux_number(&mut *cs, &s, &prod, &b)?;
+        let res = muxed.mul_number(&mut *cs, &muxed)?;
+        res.assert_equal(&mut *cs, &out.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_constant_folding_circuit() {
+    let params = {
+        let c = TestConstantFoldingCircuit { s: None, out: None };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for (s, out, expected) in [
+        (false, 1764, true),
+        (true, 49, true),
+        (false, 49, false),
+        (true, 1764, false),
+    ] {
+        let c = TestConstantFoldingCircuit {
+            s: Some(s),
+            out: Some(BellmanFr::from(out)),
+        };
+        let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+    }
+}
+
+#[derive(Clone)]
+struct TestRangeCheckManyCircuit {
+    nums: Vec<Option<BellmanFr>>,
+    allocs: Vec<u64>,
+    sum: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestRangeCheckManyCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let mut nums = Vec::new();
+        for n in self.nums {
+            nums.push(
+                AllocatedNum::alloc(&mut *cs, || n.ok_or(SynthesisError::AssignmentMissing))?
+                    .into(),
+            );
+        }
+        let sum = AllocatedNum::alloc(&mut *cs, || {
+            self.sum.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let constrained = nums
+            .into_iter()
+            .map(|n| UnsignedInteger::constrain(&mut *cs, n, 8))
+            .collect::<Result<Vec<_>, _>>()?;
+        let allocated = self
+            .allocs
+            .into_iter()
+            .map(|v| UnsignedInteger::alloc(&mut *cs, ZkScalar::from(v), 8))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut res = Number::zero();
+        for v in constrained.into_iter().chain(allocated) {
+            res = res + Number::from(v);
+        }
+        res.assert_equal(&mut *cs, &sum.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_range_check_many_circuit() {
+    let params = {
+        let c = TestRangeCheckManyCircuit {
+            nums: vec![None; 3],
+            allocs: vec![0; 2],
+            sum: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for (nums, allocs, sum, expected) in [
+        ([0, 0, 0], [0, 0], 0, true),
+        ([1, 2, 3], [4, 5], 15, true),
+        ([255, 255, 255], [255, 255], 1275, true),
+        ([1, 2, 3], [4, 5], 16, false),
+        ([256, 0, 0], [0, 0], 256, false),
+        ([0, 0, 256], [0, 0], 256, false),
+        ([300, 200, 0], [0, 0], 500, false),
+    ] {
+        let c = TestRangeCheckManyCircuit {
+            nums: nums.iter().map(|n| Some(BellmanFr::from(*n))).collect(),
+            allocs: allocs.to_vec(),
+            sum: Some(BellmanFr::from(sum)),
+        };
+        let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+    }
+
+    // Out of range values can not be allocated
+    let c = TestRangeCheckManyCircuit {
+        nums: vec![Some(BellmanFr::zero()); 3],
+        allocs: vec![256, 0],
+        sum: Some(BellmanFr::from(256)),
+    };
+    assert!(groth16::create_random_proof(c, &params, &mut OsRng).is_err());
+}
+
+#[test]
+fn test_range_check_constraints() {
+    let mut cs = CountingConstraintSystem::new();
+    UnsignedInteger::alloc(&mut cs, ZkScalar::from(1234), 64).unwrap();
+    assert_eq!(cs.num_constraints, 64);
+
+    let mut cs = CountingConstraintSystem::new();
+    let num = AllocatedNum::alloc(&mut cs, || Ok(BellmanFr::from(1234))).unwrap();
+    UnsignedInteger::constrain(&mut cs, num.into(), 64).unwrap();
+    assert_eq!(cs.num_constraints, 65);
+    assert!(cs.satisfied);
+
+    let mut cs = CountingConstraintSystem::new();
+    assert!(matches!(
+        UnsignedInteger::alloc(&mut cs, ZkScalar::from(256), 8),
+        Err(SynthesisError::Unsatisfiable)
+    ));
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ArithOp {
+    Add,
+    CheckedAdd,
+    WrappingAdd,
+    Sub,
+    CheckedSub,
+    WrappingSub,
+}
+
+#[derive(Clone)]
+struct TestUintArithCircuit {
+    op: ArithOp,
+    a: Option<BellmanFr>,
+    b: Option<BellmanFr>,
+    out: Option<BellmanFr>,
+    flag: Option<bool>,
+}
+
+impl Circuit<BellmanFr> for TestUintArithCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let a = UnsignedInteger::constrain(&mut *cs, a.into(), 8)?;
+        let b = AllocatedNum::alloc(&mut *cs, || self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = UnsignedInteger::constrain(&mut *cs, b.into(), 8)?;
+        let out = AllocatedNum::alloc(&mut *cs, || {
+            self.out.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let flag = AllocatedBit::alloc(&mut *cs, self.flag)?;
+
+        let (res, res_flag) = match self.op {
+            ArithOp::Add => (a.add(&mut *cs, &b)?, None),
+            ArithOp::CheckedAdd => {
+                let (res, overflow) = a.checked_add(&mut *cs, &b)?;
+                (res, Some(overflow))
+            }
+            ArithOp::WrappingAdd => (a.wrapping_add(&mut *cs, &b)?, None),
+            ArithOp::Sub => (a.sub(&mut *cs, &b)?, None),
+            ArithOp::CheckedSub => {
+                let (res, underflow) = a.checked_sub(&mut *cs, &b)?;
+                (res, Some(underflow))
+            }
+            ArithOp::WrappingSub => (a.wrapping_sub(&mut *cs, &b)?, None),
+        };
+        assert_eq!(res.num_bits(), 8);
+        Number::from(res).assert_equal(&mut *cs, &out.into());
+        if let Some(res_flag) = res_flag {
+            Number::from(res_flag).assert_equal(&mut *cs, &flag.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_uint_arith_circuit() {
+    use ArithOp::*;
+    for op in [Add, CheckedAdd, WrappingAdd, Sub, CheckedSub, WrappingSub] {
+        let params = {
+            let c = TestUintArithCircuit {
+                op,
+                a: None,
+                b: None,
+                out: None,
+                flag: None,
+            };
+            groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+        };
+
+        let pvk = groth16::prepare_verifying_key(&params.vk);
+
+        for (a, b) in [
+            (0u64, 0u64),
+            (10, 20),
+            (20, 10),
+            (200, 55),
+            (200, 56),
+            (255, 255),
+        ] {
+            let (out, flag) = match op {
+                Add | CheckedAdd | WrappingAdd => ((a + b) % 256, a + b > 255),
+                Sub | CheckedSub | WrappingSub => ((a + 256 - b) % 256, a < b),
+            };
+            let strict = op == Add || op == Sub;
+            let flagged = op == CheckedAdd || op == CheckedSub;
+            let valid = !(strict && flag);
+            for (claimed_out, claimed_flag, valid) in [
+                (out, flag, valid),
+                ((out + 1) % 256, flag, false),
+                (out, !flag, valid && !flagged),
+            ] {
+                let c = TestUintArithCircuit {
+                    op,
+                    a: Some(BellmanFr::from(a)),
+                    b: Some(BellmanFr::from(b)),
+                    out: Some(BellmanFr::from(claimed_out)),
+                    flag: Some(claimed_flag),
+                };
+                let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+                assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), valid);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TestMulDivRemCircuit {
+    a: Option<BellmanFr>,
+    b: Option<BellmanFr>,
+    d: Option<BellmanFr>,
+    q: Option<BellmanFr>,
+    r: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestMulDivRemCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let a = UnsignedInteger::constrain(&mut *cs, a.into(), 8)?;
+        let b = AllocatedNum::alloc(&mut *cs, || self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = UnsignedInteger::constrain(&mut *cs, b.into(), 8)?;
+        let d = AllocatedNum::alloc(&mut *cs, || self.d.ok_or(SynthesisError::AssignmentMissing))?;
+        let d = UnsignedInteger::constrain(&mut *cs, d.into(), 8)?;
+        let q = AllocatedNum::alloc(&mut *cs, || self.q.ok_or(SynthesisError::AssignmentMissing))?;
+        let r = AllocatedNum::alloc(&mut *cs, || self.r.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let prod = a.mul(&mut *cs, &b)?;
+        assert_eq!(prod.num_bits(), 16);
+        let (res_q, res_r) = prod.div_rem(&mut *cs, &d)?;
+        assert_eq!(res_q.num_bits(), 16);
+        assert_eq!(res_r.num_bits(), 8);
+        Number::from(res_q).assert_equal(&mut *cs, &q.into());
+        Number::from(res_r).assert_equal(&mut *cs, &r.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_mul_div_rem_circuit() {
+    let params = {
+        let c = TestMulDivRemCircuit {
+            a: None,
+            b: None,
+            d: None,
+            q: None,
+            r: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for (a, b, d, q, r, expected) in [
+        (0, 0, 1, 0, 0, true),
+        (255, 255, 1, 65025, 0, true),
+        (255, 255, 255, 255, 0, true),
+        (200, 150, 7, 4285, 5, true),
+        (200, 150, 7, 4285, 4, false),
+        (200, 150, 7, 4284, 12, false),
+        (13, 1, 200, 0, 13, true),
+        (13, 1, 200, 1, 13, false),
+    ] {
+        let c = TestMulDivRemCircuit {
+            a: Some(BellmanFr::from(a)),
+            b: Some(BellmanFr::from(b)),
+            d: Some(BellmanFr::from(d)),
+            q: Some(BellmanFr::from(q)),
+            r: Some(BellmanFr::from(r)),
+        };
+        let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+    }
+
+    // Division by zero
+    let c = TestMulDivRemCircuit {
+        a: Some(BellmanFr::from(10)),
+        b: Some(BellmanFr::from(10)),
+        d: Some(BellmanFr::zero()),
+        q: Some(BellmanFr::zero()),
+        r: Some(BellmanFr::zero()),
+    };
+    assert!(groth16::create_random_proof(c, &params, &mut OsRng).is_err());
+}
+
+#[derive(Clone)]
+struct TestBitwiseCircuit {
+    a: Option<BellmanFr>,
+    b: Option<BellmanFr>,
+    outs: Vec<Option<BellmanFr>>,
+}
+
+impl Circuit<BellmanFr> for TestBitwiseCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let a = UnsignedInteger::constrain(&mut *cs, a.into(), 8)?;
+        let b = AllocatedNum::alloc(&mut *cs, || self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = UnsignedInteger::constrain(&mut *cs, b.into(), 8)?;
+
+        let results = [
+            a.and(&mut *cs, &b)?,
+            a.or(&mut *cs, &b)?,
+            a.xor(&mut *cs, &b)?,
+            a.not(&mut *cs)?,
+            a.shl(&mut *cs, 3)?,
+            a.shr(&mut *cs, 3)?,
+            a.rotl(3),
+            a.rotr(3),
+            a.shl(&mut *cs, 8)?,
+        ];
+        for (res, out) in results.into_iter().zip(self.outs) {
+            assert_eq!(res.num_bits(), 8);
+            let out =
+                AllocatedNum::alloc(&mut *cs, || out.ok_or(SynthesisError::AssignmentMissing))?;
+            Number::from(res).assert_equal(&mut *cs, &out.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_bitwise_circuit() {
+    let params = {
+        let c = TestBitwiseCircuit {
+            a: None,
+            b: None,
+            outs: vec![None; 9],
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for (a, b) in [(0u8, 0u8), (0b10110011, 0b01101010), (255, 1), (1, 255)] {
+        let outs = [
+            a & b,
+            a | b,
+            a ^ b,
+            !a,
+            a << 3,
+            a >> 3,
+            a.rotate_left(3),
+            a.rotate_right(3),
+            0,
+        ];
+        for corrupt in [None, Some(0), Some(3), Some(6), Some(8)] {
+            let c = TestBitwiseCircuit {
+                a: Some(BellmanFr::from(a as u64)),
+                b: Some(BellmanFr::from(b as u64)),
+                outs: outs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, o)| {
+                        let o = if corrupt == Some(i) {
+                            o.wrapping_add(1)
+                        } else {
+                            *o
+                        };
+                        Some(BellmanFr::from(o as u64))
+                    })
+                    .collect(),
+            };
+            let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+            assert_eq!(
+                groth16::verify_proof(&pvk, &proof, &[]).is_ok(),
+                corrupt.is_none()
+            );
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TestSliceCircuit {
+    a: Option<BellmanFr>,
+    low: Option<BellmanFr>,
+    mid: Option<BellmanFr>,
+    high: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestSliceCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let a = UnsignedInteger::constrain(&mut *cs, a.into(), 16)?;
+        let low = AllocatedNum::alloc(&mut *cs, || {
+            self.low.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let mid = AllocatedNum::alloc(&mut *cs, || {
+            self.mid.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let high = AllocatedNum::alloc(&mut *cs, || {
+            self.high.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        assert!(a.slice(10, 7).is_err());
+        assert!(a.slice(17, 0).is_err());
+        assert!(a.slice(usize::MAX, 2).is_err());
+        assert!(a.extract_bits(17).is_err());
+        assert_eq!(a.slice(16, 0)?.num_bits(), 0);
+
+        Number::from(a.extract_bits(4)?).assert_equal(&mut *cs, &low.into());
+        Number::from(a.slice(4, 8)?).assert_equal(&mut *cs, &mid.into());
+        Number::from(a.slice(12, 4)?).assert_equal(&mut *cs, &high.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_slice_circuit() {
+    let params = {
+        let c = TestSliceCircuit {
+            a: None,
+            low: None,
+            mid: None,
+            high: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for (a, low, mid, high, expected) in [
+        (0xabcd, 0xd, 0xbc, 0xa, true),
+        (0xffff, 0xf, 0xff, 0xf, true),
+        (0x0f0f, 0xf, 0xf0, 0x0, true),
+        // Popcount is not the packed value
+        (0x0f0f, 0x4, 0xf0, 0x0, false),
+        (0xabcd, 0xd, 0xbc, 0xb, false),
+    ] {
+        let c = TestSliceCircuit {
+            a: Some(BellmanFr::from(a)),
+            low: Some(BellmanFr::from(low)),
+            mid: Some(BellmanFr::from(mid)),
+            high: Some(BellmanFr::from(high)),
+        };
+        let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+    }
+}
+
+#[derive(Clone)]
+struct TestMinMaxClampCircuit {
+    a: Option<BellmanFr>,
+    b: Option<BellmanFr>,
+    c: Option<BellmanFr>,
+    is_lt: Option<bool>,
+    min: Option<BellmanFr>,
+    max: Option<BellmanFr>,
+    clamped: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestMinMaxClampCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        // Different widths on purpose
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let a = UnsignedInteger::constrain(&mut *cs, a.into(), 4)?;
+        let b = AllocatedNum::alloc(&mut *cs, || self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = UnsignedInteger::constrain(&mut *cs, b.into(), 8)?;
+        let c = AllocatedNum::alloc(&mut *cs, || self.c.ok_or(SynthesisError::AssignmentMissing))?;
+        let c = UnsignedInteger::constrain(&mut *cs, c.into(), 6)?;
+        let is_lt = AllocatedBit::alloc(&mut *cs, self.is_lt)?;
+        let min = AllocatedNum::alloc(&mut *cs, || {
+            self.min.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let max = AllocatedNum::alloc(&mut *cs, || {
+            self.max.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let clamped = AllocatedNum::alloc(&mut *cs, || {
+            self.clamped.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let res_lt = a.lt(&mut *cs, &b)?;
+        Number::from(res_lt).assert_equal(&mut *cs, &is_lt.into());
+
+        let res_min = b.min(&mut *cs, &a)?;
+        assert_eq!(res_min.num_bits(), 4);
+        Number::from(res_min).assert_equal(&mut *cs, &min.into());
+
+        let res_max = a.max(&mut *cs, &b)?;
+        assert_eq!(res_max.num_bits(), 8);
+        Number::from(res_max).assert_equal(&mut *cs, &max.into());
+
+        // Clamp b into [a, c]
+        let res_clamped = b.clamp(&mut *cs, &a, &c)?;
+        assert_eq!(res_clamped.num_bits(), 6);
+        Number::from(res_clamped).assert_equal(&mut *cs, &clamped.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_min_max_clamp_circuit() {
+    let params = {
+        let c = TestMinMaxClampCircuit {
+            a: None,
+            b: None,
+            c: None,
+            is_lt: None,
+            min: None,
+            max: None,
+            clamped: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for (a, b, c) in [
+        (0u64, 0u64, 0u64),
+        (5, 200, 63),
+        (15, 255, 40),
+        (15, 3, 40),
+        (7, 7, 7),
+        (10, 12, 20),
+        (10, 12, 5),
+    ] {
+        let (is_lt, min, max) = (a < b, std::cmp::min(a, b), std::cmp::max(a, b));
+        let clamped = std::cmp::min(std::cmp::max(b, a), c);
+        for (claimed, expected) in [
+            ((is_lt, min, max, clamped), true),
+            ((!is_lt, min, max, clamped), false),
+            ((is_lt, min + 1, max, clamped), false),
+            ((is_lt, min, max + 1, clamped), false),
+            ((is_lt, min, max, clamped + 1), false),
+        ] {
+            let circuit = TestMinMaxClampCircuit {
+                a: Some(BellmanFr::from(a)),
+                b: Some(BellmanFr::from(b)),
+                c: Some(BellmanFr::from(c)),
+                is_lt: Some(claimed.0),
+                min: Some(BellmanFr::from(claimed.1)),
+                max: Some(BellmanFr::from(claimed.2)),
+                clamped: Some(BellmanFr::from(claimed.3)),
+            };
+            let proof = groth16::create_random_proof(circuit.clone(), &params, &mut OsRng).unwrap();
+            assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum SignedOp {
+    Add,
+    Sub,
+    Neg,
+    Abs,
+    IsNegative,
+    Lt,
+    Lte,
+}
+
+fn signed_to_fr(v: i64) -> BellmanFr {
+    let abs = BellmanFr::from(v.unsigned_abs());
+    if v < 0 {
+        -abs
+    } else {
+        abs
+    }
+}
+
+#[derive(Clone)]
+struct TestSignedIntegerCircuit {
+    op: SignedOp,
+    a: Option<BellmanFr>,
+    b: Option<BellmanFr>,
+    out: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestSignedIntegerCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let a = SignedInteger::constrain(&mut *cs, a.into(), 8)?;
+        let b = AllocatedNum::alloc(&mut *cs, || self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = SignedInteger::constrain(&mut *cs, b.into(), 8)?;
+        let out = AllocatedNum::alloc(&mut *cs, || {
+            self.out.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let res: Number = match self.op {
+            SignedOp::Add => a.add(&mut *cs, &b)?.into(),
+            SignedOp::Sub => a.sub(&mut *cs, &b)?.into(),
+            SignedOp::Neg => a.neg(&mut *cs)?.into(),
+            SignedOp::Abs => a.abs(&mut *cs)?.into(),
+            SignedOp::IsNegative => a.is_negative().into(),
+            SignedOp::Lt => a.lt(&mut *cs, &b)?.into(),
+            SignedOp::Lte => a.lte(&mut *cs, &b)?.into(),
+        };
+        res.assert_equal(&mut *cs, &out.into());
+
+        let c = SignedInteger::alloc(&mut *cs, signed_to_fr(-100).into(), 8)?;
+        let c_unsigned = c.to_unsigned();
+        Number::from(c_unsigned.clone()).assert_equal(&mut *cs, &BellmanFr::from(156).into());
+        let c_signed = SignedInteger::from_unsigned(&c_unsigned);
+        Number::from(c_signed).assert_equal(&mut *cs, &signed_to_fr(-100).into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_signed_integer_circuit() {
+    use SignedOp::*;
+    for op in [Add, Sub, Neg, Abs, IsNegative, Lt, Lte] {
+        let params = {
+            let c = TestSignedIntegerCircuit {
+                op,
+                a: None,
+                b: None,
+                out: None,
+            };
+            groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+        };
+
+        let pvk = groth16::prepare_verifying_key(&params.vk);
+
+        for (a, b) in [
+            (0i64, 0i64),
+            (5, -3),
+            (-5, 3),
+            (-5, -5),
+            (-128, 127),
+            (127, -128),
+            (100, 100),
+            (-100, -100),
+            (-1, 0),
+            (-128, 0),
+        ] {
+            let out = match op {
+                Add => a + b,
+                Sub => a - b,
+                Neg => -a,
+                Abs => a.abs(),
+                IsNegative => (a < 0) as i64,
+                Lt => (a < b) as i64,
+                Lte => (a <= b) as i64,
+            };
+            let out_range = if op == Abs { 0..256 } else { -128..128 };
+            let valid = out_range.contains(&out);
+            for (claimed_out, expected) in [(out, valid), (out + 1, false), (out - 256, false)] {
+                let c = TestSignedIntegerCircuit {
+                    op,
+                    a: Some(signed_to_fr(a)),
+                    b: Some(signed_to_fr(b)),
+                    out: Some(signed_to_fr(claimed_out)),
+                };
+                let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+                assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+            }
+        }
+
+        // Out of range inputs
+        let c = TestSignedIntegerCircuit {
+            op,
+            a: Some(signed_to_fr(128)),
+            b: Some(signed_to_fr(0)),
+            out: Some(signed_to_fr(0)),
+        };
+        let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+        assert!(groth16::verify_proof(&pvk, &proof, &[]).is_err());
+    }
+}
+
+#[test]
+fn test_signed_integer_alloc_range() {
+    let mut cs = bellman::gadgets::test::TestConstraintSystem::<BellmanFr>::new();
+    for v in [-129, 128, -1000, 1000] {
+        assert!(SignedInteger::alloc(&mut cs, signed_to_fr(v).into(), 8).is_err());
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum FixedOp {
+    Add,
+    Sub,
+    Mul(Rounding),
+    Div(Rounding),
+}
+
+type Fixed4 = FixedPoint<4>;
+type NativeFixed4 = NativeFixedPoint<4>;
+
+#[derive(Clone)]
+struct TestFixedPointCircuit {
+    op: FixedOp,
+    a: Option<BellmanFr>,
+    b: Option<BellmanFr>,
+    out: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestFixedPointCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let a = Fixed4::constrain(&mut *cs, a.into(), 10)?;
+        let b = AllocatedNum::alloc(&mut *cs, || self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = Fixed4::constrain(&mut *cs, b.into(), 10)?;
+        let out = AllocatedNum::alloc(&mut *cs, || {
+            self.out.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let res = match self.op {
+            FixedOp::Add => a.add(&mut *cs, &b)?,
+            FixedOp::Sub => a.sub(&mut *cs, &b)?,
+            FixedOp::Mul(rounding) => a.mul(&mut *cs, &b, rounding)?,
+            FixedOp::Div(rounding) => a.div(&mut *cs, &b, rounding)?,
+        };
+        assert_eq!(res.num_bits(), 10);
+        Number::from(res.raw().clone()).assert_equal(&mut *cs, &out.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_fixed_point_native() {
+    let n = |raw| NativeFixed4::from_raw(raw, 10).unwrap();
+    assert_eq!(NativeFixed4::from_integer(3, 10), Some(n(48)));
+    assert_eq!(NativeFixed4::from_integer(64, 10), None);
+    assert_eq!(NativeFixed4::from_raw(1024, 10), None);
+    // 1.5 * 1.0625 == 1.59375
+    assert_eq!(n(24).mul(&n(17), Rounding::Floor), Some(n(25)));
+    assert_eq!(n(24).mul(&n(17), Rounding::Ceil), Some(n(26)));
+    assert_eq!(n(24).mul(&n(17), Rounding::Nearest), Some(n(26)));
+    // 1 / 3 == 0.333...
+    assert_eq!(n(16).div(&n(48), Rounding::Floor), Some(n(5)));
+    assert_eq!(n(16).div(&n(48), Rounding::Ceil), Some(n(6)));
+    assert_eq!(n(16).div(&n(48), Rounding::Nearest), Some(n(5)));
+    assert_eq!(n(16).div(&n(0), Rounding::Floor), None);
+    assert_eq!(n(1023).mul(&n(32), Rounding::Floor), None);
+}
+
+#[test]
+fn test_fixed_point_circuit() {
+    use FixedOp::*;
+    use Rounding::*;
+    for op in [
+        Add,
+        Sub,
+        Mul(Floor),
+        Mul(Ceil),
+        Mul(Nearest),
+        Div(Floor),
+        Div(Ceil),
+        Div(Nearest),
+    ] {
+        let params = {
+            let c = TestFixedPointCircuit {
+                op,
+                a: None,
+                b: None,
+                out: None,
+            };
+            groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+        };
+
+        let pvk = groth16::prepare_verifying_key(&params.vk);
+
+        for (a, b) in [
+            (0u128, 1u128),
+            (24, 17),
+            (16, 48),
+            (40, 16),
+            (1023, 1),
+            (1023, 1023),
+            (17, 34),
+            (100, 200),
+            (200, 100),
+            (5, 0),
+        ] {
+            let (na, nb) = (
+                NativeFixed4::from_raw(a, 10).unwrap(),
+                NativeFixed4::from_raw(b, 10).unwrap(),
+            );
+            let out = match op {
+                Add => na.add(&nb),
+                Sub => na.sub(&nb),
+                Mul(rounding) => na.mul(&nb, rounding),
+                Div(rounding) => na.div(&nb, rounding),
+            };
+            let claims = match out {
+                Some(out) => vec![(out.raw(), true), (out.raw() + 1, false)],
+                None => vec![(0, false), (1023, false)],
+            };
+            for (claimed_out, expected) in claims {
+                let c = TestFixedPointCircuit {
+                    op,
+                    a: Some(BellmanFr::from(a as u64)),
+                    b: Some(BellmanFr::from(b as u64)),
+                    out: Some(BellmanFr::from(claimed_out as u64)),
+                };
+                match groth16::create_random_proof(c.clone(), &params, &mut OsRng) {
+                    Ok(proof) => {
+                        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+                    }
+                    Err(SynthesisError::DivisionByZero) => assert!(!expected),
+                    Err(e) => panic!("{:?}", e),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TestFixedPointBoundaryCircuit<const FRAC_BITS: usize> {
+    op: FixedOp,
+    a: Option<BellmanFr>,
+    b: Option<BellmanFr>,
+    out: Option<BellmanFr>,
+}
+
+impl<const FRAC_BITS: usize> Circuit<BellmanFr> for TestFixedPointBoundaryCircuit<FRAC_BITS> {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let a = FixedPoint::<FRAC_BITS>::constrain(&mut *cs, a.into(), 10)?;
+        let b = AllocatedNum::alloc(&mut *cs, || self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = FixedPoint::<FRAC_BITS>::constrain(&mut *cs, b.into(), 10)?;
+        let out = AllocatedNum::alloc(&mut *cs, || {
+            self.out.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let res = match self.op {
+            FixedOp::Mul(rounding) => a.mul(&mut *cs, &b, rounding)?,
+            FixedOp::Div(rounding) => a.div(&mut *cs, &b, rounding)?,
+            _ => unreachable!(),
+        };
+        Number::from(res.raw().clone()).assert_equal(&mut *cs, &out.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_fixed_point_boundary() {
+    // FRAC_BITS == num_bits, all values are below one
+    let n = |raw| NativeFixedPoint::<10>::from_raw(raw, 10).unwrap();
+    // 0.5 * 0.5 == 0.25, 0.25 / 0.5 == 0.5
+    assert_eq!(n(512).mul(&n(512), Rounding::Floor), Some(n(256)));
+    assert_eq!(n(1023).mul(&n(1023), Rounding::Ceil), Some(n(1023)));
+    assert_eq!(n(256).div(&n(512), Rounding::Floor), Some(n(512)));
+    assert_eq!(n(512).div(&n(256), Rounding::Floor), None);
+
+    for (op, a, b, out) in [
+        (FixedOp::Mul(Rounding::Floor), 512, 512, 256),
+        (FixedOp::Mul(Rounding::Ceil), 1023, 1023, 1023),
+        (FixedOp::Div(Rounding::Floor), 256, 512, 512),
+    ] {
+        let params = {
+            let c = TestFixedPointBoundaryCircuit::<10> {
+                op,
+                a: None,
+                b: None,
+                out: None,
+            };
+            groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+        };
+        let pvk = groth16::prepare_verifying_key(&params.vk);
+        for (claimed_out, expected) in [(out, true), (out + 1, false)] {
+            let c = TestFixedPointBoundaryCircuit::<10> {
+                op,
+                a: Some(BellmanFr::from(a)),
+                b: Some(BellmanFr::from(b)),
+                out: Some(BellmanFr::from(claimed_out)),
+            };
+            let proof = groth16::create_random_proof(c, &params, &mut OsRng).unwrap();
+            assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "FRAC_BITS <= self.num_bits")]
+fn test_fixed_point_native_too_many_frac_bits() {
+    let n = |raw| NativeFixedPoint::<11>::from_raw(raw, 10).unwrap();
+    n(512).mul(&n(512), Rounding::Floor);
+}
+
+#[test]
+#[should_panic(expected = "FRAC_BITS <= self.num_bits")]
+fn test_fixed_point_circuit_too_many_frac_bits() {
+    let c = TestFixedPointBoundaryCircuit::<11> {
+        op: FixedOp::Mul(Rounding::Floor),
+        a: None,
+        b: None,
+        out: None,
+    };
+    groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap();
+}
+
+#[derive(Clone)]
+struct TestWideIntegerCircuit {
+    a: [u64; 4],
+    b: [u64; 4],
+    small: u64,
+    sum: [u64; 4],
+    carry: bool,
+    is_lt: bool,
+    is_eq: bool,
+    a_is_small: bool,
+}
+
+impl Circuit<BellmanFr> for TestWideIntegerCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = WideUnsignedInteger::alloc_u256(&mut *cs, self.a)?;
+        let b = WideUnsignedInteger::alloc_u256(&mut *cs, self.b)?;
+        let sum = WideUnsignedInteger::alloc_u256(&mut *cs, self.sum)?;
+        let carry = AllocatedBit::alloc(&mut *cs, Some(self.carry))?;
+        let is_lt = AllocatedBit::alloc(&mut *cs, Some(self.is_lt))?;
+        let is_eq = AllocatedBit::alloc(&mut *cs, Some(self.is_eq))?;
+        let a_is_small = AllocatedBit::alloc(&mut *cs, Some(self.a_is_small))?;
+
+        let (res_sum, res_carry) = a.checked_add(&mut *cs, &b)?;
+        res_sum.assert_equal(&mut *cs, &sum);
+        Number::from(res_carry).assert_equal(&mut *cs, &carry.into());
+        let res_lt = a.lt(&mut *cs, &b)?;
+        Number::from(res_lt).assert_equal(&mut *cs, &is_lt.into());
+        let res_eq = a.is_equal(&mut *cs, &b)?;
+        Number::from(res_eq).assert_equal(&mut *cs, &is_eq.into());
+
+        let small = UnsignedInteger::alloc_64(&mut *cs, self.small)?;
+        let small_wide = WideUnsignedInteger::from_unsigned(&mut *cs, &small, 256)?;
+        let res_small = small_wide.is_equal(&mut *cs, &a)?;
+        Number::from(res_small).assert_equal(&mut *cs, &a_is_small.into());
+
+        Ok(())
+    }
+}
+
+fn add_256(a: [u64; 4], b: [u64; 4]) -> ([u64; 4], bool) {
+    let mut sum = [0u64; 4];
+    let mut carry = false;
+    for i in 0..4 {
+        let (s, c1) = a[i].overflowing_add(b[i]);
+        let (s, c2) = s.overflowing_add(carry as u64);
+        sum[i] = s;
+        carry = c1 || c2;
+    }
+    (sum, carry)
+}
+
+fn lt_256(a: [u64; 4], b: [u64; 4]) -> bool {
+    a.iter().rev().lt(b.iter().rev())
+}
+
+#[test]
+fn test_wide_integer_circuit() {
+    let params = {
+        let c = TestWideIntegerCircuit {
+            a: [0; 4],
+            b: [0; 4],
+            small: 0,
+            sum: [0; 4],
+            carry: false,
+            is_lt: false,
+            is_eq: false,
+            a_is_small: false,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    let max = u64::MAX;
+    for (a, b) in [
+        ([0, 0, 0, 0], [0, 0, 0, 0]),
+        ([123, 0, 0, 0], [123, 0, 0, 0]),
+        ([max, max, 0, 0], [1, 0, 0, 0]),
+        ([max, max, max, max], [1, 0, 0, 0]),
+        ([max, max, max, max], [max, max, max, max]),
+        ([0, 0, 0, 1], [max, max, max, 0]),
+        ([5, 0, 7, 0], [4, 0, 7, 0]),
+        ([5, 0, 7, 0], [5, 0, 7, 0]),
+        ([5, 0, 6, 0], [4, 0, 7, 0]),
+        ([0, 1, 0, 0], [max, 0, 0, 0]),
+    ] {
+        let (sum, carry) = add_256(a, b);
+        let is_lt = lt_256(a, b);
+        let correct = TestWideIntegerCircuit {
+            a,
+            b,
+            small: a[0],
+            sum,
+            carry,
+            is_lt,
+            is_eq: a == b,
+            a_is_small: a[1..] == [0, 0, 0],
+        };
+        let mut wrong_sum = correct.clone();
+        wrong_sum.sum[3] ^= 1;
+        let mut wrong_carry = correct.clone();
+        wrong_carry.carry = !carry;
+        let mut wrong_lt = correct.clone();
+        wrong_lt.is_lt = !is_lt;
+        let mut wrong_eq = correct.clone();
+        wrong_eq.is_eq = a != b;
+        let mut wrong_small = correct.clone();
+        wrong_small.a_is_small = !correct.a_is_small;
+        for (c, expected) in [
+            (correct, true),
+            (wrong_sum, false),
+            (wrong_carry, false),
+            (wrong_lt, false),
+            (wrong_eq, false),
+            (wrong_small, false),
+        ] {
+            let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+            assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TestWideNarrowingCircuit {
+    a: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestWideNarrowingCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let a = UnsignedInteger::constrain(&mut *cs, a.into(), 64)?;
+        let narrow = WideUnsignedInteger::from_unsigned(&mut *cs, &a, 32)?;
+        assert_eq!(narrow.num_bits(), 32);
+        Number::from(narrow.to_unsigned()?).assert_equal(&mut *cs, a.get_number());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_wide_narrowing_circuit() {
+    let params = {
+        let c = TestWideNarrowingCircuit { a: None };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for (a, expected) in [
+        (0u64, true),
+        (12345, true),
+        (u32::MAX as u64, true),
+        (u32::MAX as u64 + 1, false),
+        (u64::MAX, false),
+    ] {
+        let c = TestWideNarrowingCircuit {
+            a: Some(BellmanFr::from(a)),
+        };
+        let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+    }
+}
+
+#[test]
+fn test_wide_integer_alloc() {
+    let mut cs = bellman::gadgets::test::TestConstraintSystem::<BellmanFr>::new();
+    assert!(WideUnsignedInteger::alloc(&mut cs, &[0, 1], 64).is_err());
+    assert!(WideUnsignedInteger::alloc(&mut cs, &[0, 0, 0, 0, 1], 256).is_err());
+    assert!(WideUnsignedInteger::alloc(&mut cs, &[1 << 60], 60).is_err());
+}
+
+#[derive(Clone)]
+struct TestFromBooleansCircuit {
+    bits: [Option<bool>; 4],
+    out: Option<BellmanFr>,
+    out_booleans: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestFromBooleansCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let bits = self
+            .bits
+            .iter()
+            .map(|b| AllocatedBit::alloc(&mut *cs, *b))
+            .collect::<Result<Vec<_>, _>>()?;
+        let out = AllocatedNum::alloc(&mut *cs, || {
+            self.out.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let out_booleans = AllocatedNum::alloc(&mut *cs, || {
+            self.out_booleans.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let packed = UnsignedInteger::from_bits(bits.clone());
+        assert_eq!(packed.num_bits(), 4);
+        Number::from(packed).assert_equal(&mut *cs, &out.into());
+
+        // [b0, !b1, 1, b2, 0, !b3, 1]
+        let booleans = [
+            Boolean::Is(bits[0].clone()),
+            Boolean::Is(bits[1].clone()).not(),
+            Boolean::Constant(true),
+            Boolean::Is(bits[2].clone()),
+            Boolean::Constant(false),
+            Boolean::Is(bits[3].clone()).not(),
+            Boolean::Constant(true),
+        ];
+        let packed = UnsignedInteger::from_booleans(&mut *cs, &booleans)?;
+        assert_eq!(packed.num_bits(), 7);
+        Number::from(packed).assert_equal(&mut *cs, &out_booleans.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_from_booleans_circuit() {
+    let params = {
+        let c = TestFromBooleansCircuit {
+            bits: [None; 4],
+            out: None,
+            out_booleans: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for v in 0u64..16 {
+        let bits = [0, 1, 2, 3].map(|i| (v >> i) & 1 == 1);
+        let booleans = [bits[0], !bits[1], true, bits[2], false, !bits[3], true];
+        let out_booleans = booleans
+            .iter()
+            .enumerate()
+            .fold(0u64, |acc, (i, b)| acc | ((*b as u64) << i));
+        for (out, out_booleans, expected) in [
+            (v, out_booleans, true),
+            (v ^ 1, out_booleans, false),
+            (v, out_booleans ^ 4, false),
+            (v, out_booleans ^ 16, false),
+            (v, out_booleans ^ 32, false),
+        ] {
+            let c = TestFromBooleansCircuit {
+                bits: bits.map(Some),
+                out: Some(BellmanFr::from(out)),
+                out_booleans: Some(BellmanFr::from(out_booleans)),
+            };
+            let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+            assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TestBytesCircuit {
+    a: Option<BellmanFr>,
+    bytes: [u8; 4],
+    be: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestBytesCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let a = AllocatedNum::alloc(&mut *cs, || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let a = UnsignedInteger::constrain(&mut *cs, a.into(), 32)?;
+        let bytes = UInt8::alloc_many(&mut *cs, &self.bytes)?;
+        let be = AllocatedNum::alloc(&mut *cs, || {
+            self.be.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        assert!(a.extract_bits(12)?.to_bytes_le().is_err());
+
+        let le = a.to_bytes_le()?;
+        assert_eq!(le.len(), 4);
+        for (x, y) in le.iter().zip(bytes.iter()) {
+            x.get_number().assert_equal(&mut *cs, y.get_number());
+        }
+        let a_be = a.to_bytes_be()?;
+        for (x, y) in a_be.iter().rev().zip(bytes.iter()) {
+            x.get_number().assert_equal(&mut *cs, y.get_number());
+        }
+
+        Number::from(UnsignedInteger::from_bytes(&bytes)).assert_equal(&mut *cs, a.get_number());
+        Number::from(UnsignedInteger::from_bytes_be(&bytes)).assert_equal(&mut *cs, &be.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_bytes_circuit() {
+    let params = {
+        let c = TestBytesCircuit {
+            a: None,
+            bytes: [0; 4],
+            be: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for (a, bytes, be, expected) in [
+        (0x12345678u32, [0x78, 0x56, 0x34, 0x12], 0x78563412u32, true),
+        (0xff00aa01, [0x01, 0xaa, 0x00, 0xff], 0x01aa00ff, true),
+        (0x12345678, [0x12, 0x34, 0x56, 0x78], 0x12345678, false),
+        (0x12345678, [0x78, 0x56, 0x34, 0x12], 0x12345678, false),
+        (0x12345678, [0x78, 0x56, 0x34, 0x13], 0x78563413, false),
+    ] {
+        let c = TestBytesCircuit {
+            a: Some(BellmanFr::from(a as u64)),
+            bytes,
+            be: Some(BellmanFr::from(be as u64)),
+        };
+        let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+    }
+}
+
+#[derive(Clone)]
+struct TestMuxNCircuit {
+    index: Option<BellmanFr>,
+    options: [Option<BellmanFr>; 8],
+    out_4: Option<BellmanFr>,
+    out_8: Option<BellmanFr>,
+    out_const_8: Option<BellmanFr>,
+    out_array: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestMuxNCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let index = AllocatedNum::alloc(&mut *cs, || {
+            self.index.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let index = UnsignedInteger::constrain(&mut *cs, index.into(), 4)?;
+        let options = self
+            .options
+            .iter()
+            .map(|o| {
+                AllocatedNum::alloc(&mut *cs, || o.ok_or(SynthesisError::AssignmentMissing))
+                    .map(Number::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let outs = [self.out_4, self.out_8, self.out_const_8, self.out_array]
+            .iter()
+            .map(|o| {
+                AllocatedNum::alloc(&mut *cs, || o.ok_or(SynthesisError::AssignmentMissing))
+                    .map(Number::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let bits: Vec<Boolean> = index
+            .bits()
+            .iter()
+            .map(|b| Boolean::Is(b.clone()))
+            .collect();
+
+        mux_n(&mut *cs, &bits[..2], &options[..4])?.assert_equal(&mut *cs, &outs[0]);
+        mux_n(&mut *cs, &bits[..3], &options)?.assert_equal(&mut *cs, &outs[1]);
+        let table: Vec<Number> = [3, 1, 4, 1, 5, 9, 2, 6]
+            .iter()
+            .map(|v| Number::from(BellmanFr::from(*v)))
+            .collect();
+        mux_n(&mut *cs, &bits[..3], &table)?.assert_equal(&mut *cs, &outs[2]);
+
+        // 5 elements and a 4-bit index, anything above 4 is out of range
+        select_by_index(&mut *cs, &index, &options[..5])?.assert_equal(&mut *cs, &outs[3]);
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_mux_n_circuit() {
+    let params = {
+        let c = TestMuxNCircuit {
+            index: None,
+            options: [None; 8],
+            out_4: None,
+            out_8: None,
+            out_const_8: None,
+            out_array: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    let options = [10u64, 20, 30, 40, 50, 60, 70, 80];
+    let table = [3u64, 1, 4, 1, 5, 9, 2, 6];
+    for index in 0..16usize {
+        let out_4 = options[index % 4];
+        let out_8 = options[index % 8];
+        let out_const_8 = table[index % 8];
+        let out_array = if index < 5 { options[index] } else { 0 };
+        for (outs, expected) in [
+            ([out_4, out_8, out_const_8, out_array], true),
+            ([out_4 + 1, out_8, out_const_8, out_array], false),
+            ([out_4, out_8 + 1, out_const_8, out_array], false),
+            ([out_4, out_8, out_const_8 + 1, out_array], false),
+            ([out_4, out_8, out_const_8, out_array + 1], false),
+        ] {
+            let c = TestMuxNCircuit {
+                index: Some(BellmanFr::from(index as u64)),
+                options: options.map(|o| Some(BellmanFr::from(o))),
+                out_4: Some(BellmanFr::from(outs[0])),
+                out_8: Some(BellmanFr::from(outs[1])),
+                out_const_8: Some(BellmanFr::from(outs[2])),
+                out_array: Some(BellmanFr::from(outs[3])),
+            };
+            let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+            assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+        }
+    }
+}
+
+#[test]
+fn test_mux_n_constant_lookup() {
+    let mut cs = bellman::gadgets::test::TestConstraintSystem::<BellmanFr>::new();
+    let bits = [true, false, true]
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            AllocatedBit::alloc(&mut cs.namespace(|| i.to_string()), Some(*b)).map(Boolean::Is)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let table: Vec<Number> = [3, 1, 4, 1, 5, 9, 2, 6]
+        .iter()
+        .map(|v| Number::from(BellmanFr::from(*v)))
+        .collect();
+    let num_constraints = cs.num_constraints();
+
+    let res = mux_n(&mut cs.namespace(|| "4"), &bits[..2], &table[..4]).unwrap();
+    assert_eq!(res.get_value(), Some(BellmanFr::from(1)));
+    assert_eq!(cs.num_constraints(), num_constraints + 1);
+
+    let res = mux_n(&mut cs.namespace(|| "8"), &bits, &table).unwrap();
+    assert_eq!(res.get_value(), Some(BellmanFr::from(9)));
+    assert_eq!(cs.num_constraints(), num_constraints + 3);
+    assert!(cs.is_satisfied());
+}
+
+#[derive(Clone)]
+struct TestWriteByIndexCircuit {
+    index: Option<BellmanFr>,
+    value: Option<BellmanFr>,
+    out: [Option<BellmanFr>; 5],
+    out_of_bounds: Option<bool>,
+}
+
+impl Circuit<BellmanFr> for TestWriteByIndexCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let index = AllocatedNum::alloc(&mut *cs, || {
+            self.index.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let index = UnsignedInteger::constrain(&mut *cs, index.into(), 3)?;
+        let value = AllocatedNum::alloc(&mut *cs, || {
+            self.value.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let out = self
+            .out
+            .iter()
+            .map(|o| {
+                AllocatedNum::alloc(&mut *cs, || o.ok_or(SynthesisError::AssignmentMissing))
+                    .map(Number::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let out_of_bounds = AllocatedBit::alloc(&mut *cs, self.out_of_bounds)?;
+
+        let array: Vec<Number> = (1..=5)
+            .map(|v| Number::from(BellmanFr::from(v * 10)))
+            .collect();
+        let (res, res_out_of_bounds) = write_by_index(&mut *cs, &array, &index, &value.into())?;
+        for (r, o) in res.iter().zip(out.iter()) {
+            r.assert_equal(&mut *cs, o);
+        }
+        Number::from(res_out_of_bounds).assert_equal(&mut *cs, &out_of_bounds.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_write_by_index_circuit() {
+    let params = {
+        let c = TestWriteByIndexCircuit {
+            index: None,
+            value: None,
+            out: [None; 5],
+            out_of_bounds: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for index in 0..8usize {
+        let mut out = [10u64, 20, 30, 40, 50];
+        if index < 5 {
+            out[index] = 123;
+        }
+        let out_of_bounds = index >= 5;
+        let mut wrong_write = out;
+        wrong_write[(index + 1) % 5] = 123;
+        let mut wrong_value = out;
+        wrong_value[index % 5] += 1;
+        for (out, out_of_bounds, expected) in [
+            (out, out_of_bounds, true),
+            (out, !out_of_bounds, false),
+            (wrong_write, out_of_bounds, false),
+            (wrong_value, out_of_bounds, false),
+        ] {
+            let c = TestWriteByIndexCircuit {
+                index: Some(BellmanFr::from(index as u64)),
+                value: Some(BellmanFr::from(123)),
+                out: out.map(|o| Some(BellmanFr::from(o))),
+                out_of_bounds: Some(out_of_bounds),
+            };
+            let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+            assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum CondKind {
+    Is,
+    Not,
+    Constant,
+}
+
+#[derive(Clone)]
+struct TestCswapCircuit {
+    kind: CondKind,
+    cond: Option<bool>,
+    a: [Option<BellmanFr>; 2],
+    b: [Option<BellmanFr>; 2],
+    swapped: [Option<BellmanFr>; 2],
+    muxed: [Option<BellmanFr>; 2],
+}
+
+impl Circuit<BellmanFr> for TestCswapCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let mut alloc = |v: &[Option<BellmanFr>; 2]| {
+            v.iter()
+                .map(|v| {
+                    AllocatedNum::alloc(&mut *cs, || v.ok_or(SynthesisError::AssignmentMissing))
+                        .map(Number::from)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let (a, b) = (alloc(&self.a)?, alloc(&self.b)?);
+        let (swapped, muxed) = (alloc(&self.swapped)?, alloc(&self.muxed)?);
+        let cond = match self.kind {
+            CondKind::Is => Boolean::Is(AllocatedBit::alloc(&mut *cs, self.cond)?),
+            CondKind::Not => {
+                Boolean::Is(AllocatedBit::alloc(&mut *cs, self.cond.map(|c| !c))?).not()
+            }
+            CondKind::Constant => Boolean::Constant(self.cond.unwrap_or(false)),
+        };
+
+        // Constant conditions leave some of the inputs unused
+        let all = a[1].clone() + b[1].clone();
+        all.assert_equal(&mut *cs, &all);
+
+        let (x, y) = cswap(&mut *cs, &cond, &a[0], &b[0])?;
+        x.assert_equal(&mut *cs, &swapped[0]);
+        y.assert_equal(&mut *cs, &swapped[1]);
+        let res = mux_vec(&mut *cs, &cond, &a, &b)?;
+        for (r, m) in res.iter().zip(muxed.iter()) {
+            r.assert_equal(&mut *cs, m);
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_cswap_circuit() {
+    for kind in [CondKind::Is, CondKind::Not, CondKind::Constant] {
+        for cond in [false, true] {
+            let params = {
+                let c = TestCswapCircuit {
+                    kind,
+                    cond: Some(cond),
+                    a: [None; 2],
+                    b: [None; 2],
+                    swapped: [None; 2],
+                    muxed: [None; 2],
+                };
+                groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+            };
+
+            let pvk = groth16::prepare_verifying_key(&params.vk);
+
+            let (a, b) = ([1u64, 2], [3u64, 4]);
+            let swapped = if cond { [b[0], a[0]] } else { [a[0], b[0]] };
+            let muxed = if cond { b } else { a };
+            for (claimed_cond, swapped, muxed, expected) in [
+                (cond, swapped, muxed, true),
+                (cond, [swapped[1], swapped[0]], muxed, false),
+                (cond, swapped, [muxed[0] + 1, muxed[1]], false),
+                (cond, swapped, [muxed[0], muxed[1] + 1], false),
+                (!cond, swapped, muxed, false),
+            ] {
+                if matches!(kind, CondKind::Constant) && claimed_cond != cond {
+                    continue;
+                }
+                let c = TestCswapCircuit {
+                    kind,
+                    cond: Some(claimed_cond),
+                    a: a.map(|v| Some(BellmanFr::from(v))),
+                    b: b.map(|v| Some(BellmanFr::from(v))),
+                    swapped: swapped.map(|v| Some(BellmanFr::from(v))),
+                    muxed: muxed.map(|v| Some(BellmanFr::from(v))),
+                };
+                let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+                assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TestBooleanAggregatesCircuit {
+    bits: [Option<bool>; 5],
+    k: usize,
+    flags: [Option<bool>; 4],
+    count: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestBooleanAggregatesCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        // Mix Is, Not and constant bits
+        let mut bits = Vec::new();
+        for (i, b) in self.bits.iter().enumerate() {
+            let bit = if i % 2 == 0 {
+                Boolean::Is(AllocatedBit::alloc(&mut *cs, *b)?)
+            } else {
+                Boolean::Is(AllocatedBit::alloc(&mut *cs, b.map(|b| !b))?).not()
+            };
+            bits.push(bit);
+        }
+        bits.push(Boolean::Constant(false));
+        let flags = self
+            .flags
+            .iter()
+            .map(|f| AllocatedBit::alloc(&mut *cs, *f))
+            .collect::<Result<Vec<_>, _>>()?;
+        let count = AllocatedNum::alloc(&mut *cs, || {
+            self.count.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        count_true(&bits).assert_equal(&mut *cs, &count.into());
+        let results = [
+            all_of(&mut *cs, &bits[..5])?,
+            any_of(&mut *cs, &bits)?,
+            exactly_one(&mut *cs, &bits)?,
+            at_least_k_of_n(&mut *cs, &bits, self.k)?,
+        ];
+        for (r, f) in results.iter().zip(flags.iter()) {
+            Number::from(r.clone()).assert_equal(&mut *cs, &f.clone().into());
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_boolean_aggregates_circuit() {
+    for k in 0..=7 {
+        let params = {
+            let c = TestBooleanAggregatesCircuit {
+                bits: [None; 5],
+                k,
+                flags: [None; 4],
+                count: None,
+            };
+            groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+        };
+
+        let pvk = groth16::prepare_verifying_key(&params.vk);
+
+        for v in [0u32, 1, 4, 5, 16, 31, 7, 24] {
+            let bits = [0, 1, 2, 3, 4].map(|i| (v >> i) & 1 == 1);
+            let count = v.count_ones() as usize;
+            let flags = [count == 5, count > 0, count == 1, count >= k];
+            for i in 0..5 {
+                let mut wrong_flags = flags;
+                let mut wrong_count = count;
+                if i < 4 {
+                    wrong_flags[i] = !wrong_flags[i];
+                } else {
+                    wrong_count += 1;
+                }
+                for (flags, count, expected) in
+                    [(flags, count, true), (wrong_flags, wrong_count, false)]
+                {
+                    let c = TestBooleanAggregatesCircuit {
+                        bits: bits.map(Some),
+                        k,
+                        flags: flags.map(Some),
+                        count: Some(BellmanFr::from(count as u64)),
+                    };
+                    let proof =
+                        groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+                    assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TestSortCircuit {
+    vals: Vec<Option<BellmanFr>>,
+    sorted: Vec<Option<BellmanFr>>,
+}
+
+impl Circuit<BellmanFr> for TestSortCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let mut vals = Vec::new();
+        for v in self.vals.iter() {
+            let v = AllocatedNum::alloc(&mut *cs, || v.ok_or(SynthesisError::AssignmentMissing))?;
+            vals.push(UnsignedInteger::constrain(&mut *cs, v.into(), 8)?);
+        }
+        let res = sort(&mut *cs, &vals)?;
+        for (r, s) in res.iter().zip(self.sorted.iter()) {
+            let s = AllocatedNum::alloc(&mut *cs, || s.ok_or(SynthesisError::AssignmentMissing))?;
+            assert_eq!(r.num_bits(), 8);
+            r.get_number().assert_equal(&mut *cs, &s.into());
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sort_circuit() {
+    for n in [1, 2, 5, 8] {
+        let params = {
+            let c = TestSortCircuit {
+                vals: vec![None; n],
+                sorted: vec![None; n],
+            };
+            groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+        };
+
+        let pvk = groth16::prepare_verifying_key(&params.vk);
+
+        for vals in [
+            vec![5u64, 3, 255, 0, 3, 7, 200, 1],
+            vec![0, 1, 2, 3, 4, 5, 6, 7],
+            vec![7, 6, 5, 4, 3, 2, 1, 0],
+            vec![9, 9, 9, 9, 9, 9, 9, 9],
+        ] {
+            let vals = vals[..n].to_vec();
+            let mut sorted = vals.clone();
+            sorted.sort();
+            let mut unsorted = sorted.clone();
+            unsorted.reverse();
+            let mut wrong = sorted.clone();
+            wrong[0] += 1;
+            for (claimed, expected) in [
+                (sorted.clone(), true),
+                (unsorted.clone(), unsorted == sorted),
+                (wrong, false),
+            ] {
+                let c = TestSortCircuit {
+                    vals: vals.iter().map(|v| Some(BellmanFr::from(*v))).collect(),
+                    sorted: claimed.iter().map(|v| Some(BellmanFr::from(*v))).collect(),
+                };
+                let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+                assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TestPermutationCircuit {
+    a: [Option<BellmanFr>; 5],
+    b: [Option<BellmanFr>; 5],
+}
+
+impl Circuit<BellmanFr> for TestPermutationCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let mut alloc = |v: &[Option<BellmanFr>; 5]| {
+            v.iter()
+                .map(|v| {
+                    AllocatedNum::alloc(&mut *cs, || v.ok_or(SynthesisError::AssignmentMissing))
+                        .map(Number::from)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let (a, b) = (alloc(&self.a)?, alloc(&self.b)?);
+        assert_permutation(&mut *cs, &a, &b)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_permutation_circuit() {
+    let params = {
+        let c = TestPermutationCircuit {
+            a: [None; 5],
+            b: [None; 5],
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for (a, b, expected) in [
+        ([1u64, 2, 3, 4, 5], [5u64, 4, 3, 2, 1], true),
+        ([1, 2, 3, 4, 5], [1, 2, 3, 4, 5], true),
+        ([7, 7, 0, 1, 7], [7, 0, 7, 7, 1], true),
+        ([1, 2, 3, 4, 5], [1, 2, 3, 4, 6], false),
+        ([7, 7, 0, 1, 7], [7, 0, 0, 7, 1], false),
+        ([1, 1, 2, 2, 3], [1, 2, 2, 3, 3], false),
+        ([0, 0, 0, 0, 0], [0, 0, 0, 0, 1], false),
+    ] {
+        let c = TestPermutationCircuit {
+            a: a.map(|v| Some(BellmanFr::from(v))),
+            b: b.map(|v| Some(BellmanFr::from(v))),
+        };
+        let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+    }
+}
+
+#[derive(Clone)]
+struct TestInSetCircuit {
+    x: Option<BellmanFr>,
+    is_in: Option<bool>,
+    assert_in: bool,
+}
+
+impl Circuit<BellmanFr> for TestInSetCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let x = AllocatedNum::alloc(&mut *cs, || self.x.ok_or(SynthesisError::AssignmentMissing))?;
+        let x: Number = x.into();
+        let is_in = AllocatedBit::alloc(&mut *cs, self.is_in)?;
+        let set = [3, 10, 42, 1000].map(BellmanFr::from);
+
+        let res = is_in_set(&mut *cs, &x, &set)?;
+        Number::from(res).assert_equal(&mut *cs, &is_in.into());
+        assert_eq!(is_in_set(&mut *cs, &x, &[])?.get_value(), Some(false));
+        if self.assert_in {
+            assert_in_set(&mut *cs, &x, &set)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_in_set_circuit() {
+    for assert_in in [false, true] {
+        let params = {
+            let c = TestInSetCircuit {
+                x: None,
+                is_in: None,
+                assert_in,
+            };
+            groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+        };
+
+        let pvk = groth16::prepare_verifying_key(&params.vk);
+
+        for x in [0u64, 3, 4, 10, 42, 43, 999, 1000] {
+            let is_in = [3, 10, 42, 1000].contains(&x);
+            for (claimed, expected) in [(is_in, !assert_in || is_in), (!is_in, false)] {
+                let c = TestInSetCircuit {
+                    x: Some(BellmanFr::from(x)),
+                    is_in: Some(claimed),
+                    assert_in,
+                };
+                let proof = groth16::create_random_proof(c.clone(), &params, &mut OsRng).unwrap();
+                assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_lt_field_constraints() {
+    for (a, b, lt) in [
+        (BellmanFr::from(123), -BellmanFr::one(), true),
+        (-BellmanFr::one(), BellmanFr::from(123), false),
+        (BellmanFr::zero(), BellmanFr::zero(), false),
+    ] {
+        let mut cs = CountingConstraintSystem::new();
+        let a = AllocatedNum::alloc(&mut cs, || Ok(a)).unwrap();
+        let b = AllocatedNum::alloc(&mut cs, || Ok(b)).unwrap();
+        let res = Number::from(a).lt_field(&mut cs, &b.into()).unwrap();
+        assert_eq!(res.get_value(), Some(lt));
+        assert!(cs.satisfied);
+        assert_eq!(cs.num_constraints, 1044);
+    }
+}
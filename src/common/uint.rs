@@ -2,9 +2,8 @@ use super::*;
 use crate::BellmanFr;
 use bazuka::zk::ZkScalar;
 use bellman::gadgets::boolean::{AllocatedBit, Boolean};
-use bellman::gadgets::num::AllocatedNum;
 use bellman::{ConstraintSystem, LinearCombination, SynthesisError};
-use ff::PrimeFieldBits;
+use ff::{PrimeField, PrimeFieldBits};
 
 #[derive(Clone)]
 pub struct UnsignedInteger {
@@ -28,21 +27,98 @@ impl UnsignedInteger {
     pub fn num_bits(&self) -> usize {
         self.bits.len()
     }
-    pub fn extract_bits(&self, count: usize) -> Self {
+    // Packs already constrained bits (Little-endian), 0 constraints
+    pub fn from_bits(bits: Vec<AllocatedBit>) -> Self {
         let mut num = Number::zero();
-        let bits = self.bits[..count].iter().cloned().collect::<Vec<_>>();
+        let mut coeff = BellmanFr::one();
         for b in bits.iter() {
-            num = num + Number::from(b.clone());
+            num = num + (coeff, Number::from(b.clone()));
+            coeff = coeff.double();
+        }
+        Self { bits, num }
+    }
+    // Same as `from_bits`, but `Boolean::Not` bits cost 1 constraint each, and constant
+    // bits 2 constraints per distinct value
+    pub fn from_booleans<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        bits: &[Boolean],
+    ) -> Result<Self, SynthesisError> {
+        let mut constants: [Option<AllocatedBit>; 2] = [None, None];
+        let mut allocated = Vec::new();
+        for bit in bits {
+            allocated.push(match bit {
+                Boolean::Is(b) => b.clone(),
+                Boolean::Not(b) => AllocatedBit::nor(&mut *cs, b, b)?,
+                Boolean::Constant(v) => match &constants[*v as usize] {
+                    Some(b) => b.clone(),
+                    None => {
+                        let b = AllocatedBit::alloc(&mut *cs, Some(*v))?;
+                        Number::from(b.clone())
+                            .assert_equal(&mut *cs, &Number::from(Boolean::Constant(*v)));
+                        constants[*v as usize] = Some(b.clone());
+                        b
+                    }
+                },
+            });
+        }
+        Ok(Self::from_bits(allocated))
+    }
+    // The lowest `count` bits
+    pub fn extract_bits(&self, count: usize) -> Result<Self, SynthesisError> {
+        self.slice(0, count)
+    }
+    // `len` bits starting from bit `start` (Little-endian), 0 constraints
+    pub fn slice(&self, start: usize, len: usize) -> Result<Self, SynthesisError> {
+        let end = start
+            .checked_add(len)
+            .filter(|end| *end <= self.num_bits())
+            .ok_or(SynthesisError::Unsatisfiable)?;
+        Ok(Self::from_bits(self.bits[start..end].to_vec()))
+    }
+    // Reuses the existing bits, 0 constraints. Fails if num_bits is not a multiple of 8.
+    pub fn to_bytes_le(&self) -> Result<Vec<UInt8>, SynthesisError> {
+        if !self.num_bits().is_multiple_of(8) {
+            return Err(SynthesisError::Unsatisfiable);
         }
-        Self { num, bits }
+        self.bits
+            .chunks(8)
+            .map(|c| UInt8::from_unsigned(Self::from_bits(c.to_vec())))
+            .collect()
     }
+    pub fn to_bytes_be(&self) -> Result<Vec<UInt8>, SynthesisError> {
+        let mut bytes = self.to_bytes_le()?;
+        bytes.reverse();
+        Ok(bytes)
+    }
+    // `bytes` are little-endian, 0 constraints
+    pub fn from_bytes(bytes: &[UInt8]) -> Self {
+        Self::from_bits(
+            bytes
+                .iter()
+                .flat_map(|b| b.bits().iter().cloned())
+                .collect(),
+        )
+    }
+    pub fn from_bytes_be(bytes: &[UInt8]) -> Self {
+        let le: Vec<UInt8> = bytes.iter().rev().cloned().collect();
+        Self::from_bytes(&le)
+    }
+    // The number is derived from freshly allocated bits, so no packing constraint
+    // is needed, num_bits constraints (`constrain` needs num_bits + 1). Unlike
+    // `constrain`, an out of range val does not produce an unsatisfiable proof, it
+    // fails with `SynthesisError::Unsatisfiable` at synthesis time (Same for
+    // `alloc_32` and `alloc_64`)
     pub fn alloc<CS: ConstraintSystem<BellmanFr>>(
         cs: &mut CS,
         val: ZkScalar,
         bits: usize,
     ) -> Result<Self, SynthesisError> {
-        let alloc = AllocatedNum::alloc(&mut *cs, || Ok(val.into()))?;
-        Self::constrain(cs, alloc.into(), bits)
+        let val: BellmanFr = val.into();
+        if val.to_le_bits().iter().skip(bits).any(|b| *b) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let (bits, num) = Self::alloc_bits(cs, Some(val), bits)?;
+        Ok(Self { bits, num })
     }
     pub fn alloc_32<CS: ConstraintSystem<BellmanFr>>(
         cs: &mut CS,
@@ -61,36 +137,38 @@ impl UnsignedInteger {
         num: Number,
     ) -> Result<Self, SynthesisError> {
         let as_alloc = num.compress(&mut *cs)?;
-        let mut bits = Vec::new();
-        for bit in as_alloc.to_bits_le_strict(&mut *cs)? {
-            if let Boolean::Is(bit) = bit {
-                bits.push(bit);
-            } else {
-                return Err(SynthesisError::Unsatisfiable);
-            }
-        }
+        let bits = as_alloc.to_bits_le_strict(&mut *cs)?;
+        let bits = Self::from_booleans(&mut *cs, &bits)?.bits;
         Ok(UnsignedInteger { bits, num })
     }
-    pub fn constrain<CS: ConstraintSystem<BellmanFr>>(
+    pub(super) fn alloc_bits<CS: ConstraintSystem<BellmanFr>>(
         cs: &mut CS,
-        num: Number,
+        val: Option<BellmanFr>,
         num_bits: usize,
-    ) -> Result<Self, SynthesisError> {
+    ) -> Result<(Vec<AllocatedBit>, Number), SynthesisError> {
         let mut bits = Vec::new();
         let mut coeff = BellmanFr::one();
-        let mut all = LinearCombination::<BellmanFr>::zero();
-        let bit_vals: Option<Vec<bool>> = num
-            .get_value()
-            .map(|v| v.to_le_bits().iter().map(|b| *b).collect());
+        let mut all = Number::zero();
+        let bit_vals: Option<Vec<bool>> = val.map(|v| v.to_le_bits().iter().map(|b| *b).collect());
         for i in 0..num_bits {
             let bit = AllocatedBit::alloc(&mut *cs, bit_vals.as_ref().map(|b| b[i]))?;
-            all = all + (coeff, bit.get_variable());
+            all = all + (coeff, Number::from(bit.clone()));
             bits.push(bit);
             coeff = coeff.double();
         }
+        Ok((bits, all))
+    }
+
+    // num_bits + 1 constraints
+    pub fn constrain<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        num: Number,
+        num_bits: usize,
+    ) -> Result<Self, SynthesisError> {
+        let (bits, all) = Self::alloc_bits(&mut *cs, num.get_value(), num_bits)?;
         cs.enforce(
             || "check",
-            |lc| lc + &all,
+            |lc| lc + all.get_lc(),
             |lc| lc + CS::one(),
             |lc| lc + num.get_lc(),
         );
@@ -98,22 +176,15 @@ impl UnsignedInteger {
         Ok(Self { num, bits })
     }
 
-    // ~198 constraints
+    // Operands of different widths are compared at the larger width,
+    // max(num_bits) + 3 constraints
     pub fn lt<CS: ConstraintSystem<BellmanFr>>(
         &self,
         cs: &mut CS,
         other: &UnsignedInteger,
     ) -> Result<Boolean, SynthesisError> {
-        assert_eq!(self.num_bits(), other.num_bits());
-        let num_bits = self.num_bits();
-
-        // Imagine a and b are two sigend (num_bits + 1) bits numbers
-        let two_bits = BellmanFr::from(2).pow_vartime(&[num_bits as u64 + 1, 0, 0, 0]);
-        let mut sub = self.num.clone() - other.num.clone();
-        sub.add_constant::<CS>(two_bits);
-
-        let sub_bits = UnsignedInteger::constrain(&mut *cs, sub, num_bits + 2)?;
-        Ok(Boolean::Is(sub_bits.bits()[num_bits].clone()))
+        let num_bits = std::cmp::max(self.num_bits(), other.num_bits());
+        lt_numbers(cs, &self.num, &other.num, num_bits)
     }
 
     pub fn gt<CS: ConstraintSystem<BellmanFr>>(
@@ -139,4 +210,308 @@ impl UnsignedInteger {
     ) -> Result<Boolean, SynthesisError> {
         Ok(self.lt(cs, other)?.not())
     }
+
+    // Has the width of the narrower operand, min(num_bits) + max(num_bits) + 5 constraints
+    pub fn min<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        let is_lt = self.lt(&mut *cs, other)?;
+        let ret = mux_number(&mut *cs, &is_lt, &other.num, &self.num)?;
+        let num_bits = std::cmp::min(self.num_bits(), other.num_bits());
+        UnsignedInteger::constrain(&mut *cs, ret, num_bits)
+    }
+
+    // Has the width of the wider operand, 2 * max(num_bits) + 5 constraints
+    pub fn max<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        let is_lt = self.lt(&mut *cs, other)?;
+        let ret = mux_number(&mut *cs, &is_lt, &self.num, &other.num)?;
+        let num_bits = std::cmp::max(self.num_bits(), other.num_bits());
+        UnsignedInteger::constrain(&mut *cs, ret, num_bits)
+    }
+
+    // min(max(self, low), high), never wider than `high`. Returns `high` when low > high.
+    pub fn clamp<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        low: &UnsignedInteger,
+        high: &UnsignedInteger,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        self.max(&mut *cs, low)?.min(&mut *cs, high)
+    }
+
+    // Unsatisfiable on overflow, num_bits + 1 constraints
+    pub fn add<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        assert_eq!(self.num_bits(), other.num_bits());
+        let sum = self.num.clone() + other.num.clone();
+        UnsignedInteger::constrain(&mut *cs, sum, self.num_bits())
+    }
+
+    // Returns (self + other) mod 2^num_bits and the overflow flag, num_bits + 2 constraints
+    pub fn checked_add<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+    ) -> Result<(UnsignedInteger, Boolean), SynthesisError> {
+        assert_eq!(self.num_bits(), other.num_bits());
+        let num_bits = self.num_bits();
+        let sum = self.num.clone() + other.num.clone();
+        let mut sum_bits = UnsignedInteger::constrain(&mut *cs, sum, num_bits + 1)?.bits;
+        let overflow = Boolean::Is(sum_bits.pop().unwrap());
+        Ok((Self::from_bits(sum_bits), overflow))
+    }
+
+    pub fn wrapping_add<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        Ok(self.checked_add(cs, other)?.0)
+    }
+
+    // Unsatisfiable on underflow, num_bits + 1 constraints
+    pub fn sub<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        assert_eq!(self.num_bits(), other.num_bits());
+        let sub = self.num.clone() - other.num.clone();
+        UnsignedInteger::constrain(&mut *cs, sub, self.num_bits())
+    }
+
+    // Returns (self - other) mod 2^num_bits and the underflow flag, num_bits + 2 constraints
+    pub fn checked_sub<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+    ) -> Result<(UnsignedInteger, Boolean), SynthesisError> {
+        assert_eq!(self.num_bits(), other.num_bits());
+        let num_bits = self.num_bits();
+
+        // self - other + 2^num_bits has its top bit set iff there is no underflow
+        let sub = self.num.clone() - other.num.clone() + Number::from(pow2(num_bits));
+        let mut sub_bits = UnsignedInteger::constrain(&mut *cs, sub, num_bits + 1)?.bits;
+        let underflow = Boolean::Is(sub_bits.pop().unwrap()).not();
+        Ok((Self::from_bits(sub_bits), underflow))
+    }
+
+    pub fn wrapping_sub<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        Ok(self.checked_sub(cs, other)?.0)
+    }
+
+    // The result is widened to self.num_bits() + other.num_bits() bits so it can
+    // never overflow, num_bits + 2 constraints
+    pub fn mul<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        let num_bits = self.num_bits() + other.num_bits();
+        assert!(num_bits < BellmanFr::CAPACITY as usize);
+        let prod = self.num.mul_number(&mut *cs, &other.num)?;
+        UnsignedInteger::constrain(&mut *cs, prod, num_bits)
+    }
+
+    // Returns (quotient, remainder) with the widths of self and divisor, proven through
+    // quotient * divisor + remainder == self and remainder < divisor. Division by
+    // zero is unsatisfiable. Both widths should fit in 128 bits.
+    pub fn div_rem<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        divisor: &UnsignedInteger,
+    ) -> Result<(UnsignedInteger, UnsignedInteger), SynthesisError> {
+        assert!(self.num_bits() <= 128 && divisor.num_bits() <= 128);
+        assert!(self.num_bits() + divisor.num_bits() < BellmanFr::CAPACITY as usize);
+        let (q_val, r_val) = match self.get_value().zip(divisor.get_value()) {
+            Some((n, d)) => {
+                let (n, d) = (to_u128(n), to_u128(d));
+                if d == 0 {
+                    return Err(SynthesisError::DivisionByZero);
+                }
+                (Some(n / d), Some(n % d))
+            }
+            None => (None, None),
+        };
+        let (q_bits, q) = Self::alloc_bits(&mut *cs, q_val.map(u128_to_fr), self.num_bits())?;
+        let (r_bits, r) = Self::alloc_bits(&mut *cs, r_val.map(u128_to_fr), divisor.num_bits())?;
+        let quotient = UnsignedInteger {
+            bits: q_bits,
+            num: q,
+        };
+        let remainder = UnsignedInteger {
+            bits: r_bits,
+            num: r,
+        };
+
+        // Can not wrap around the field, since both sides are less than 2^253
+        cs.enforce(
+            || "quotient * divisor == self - remainder",
+            |lc| lc + quotient.get_lc(),
+            |lc| lc + divisor.get_lc(),
+            |lc| lc + self.get_lc() - remainder.get_lc(),
+        );
+        let is_lt = remainder.lt(&mut *cs, divisor)?;
+        assert_true(&mut *cs, &is_lt);
+
+        Ok((quotient, remainder))
+    }
+
+    fn bitwise<CS: ConstraintSystem<BellmanFr>, F>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+        f: F,
+    ) -> Result<UnsignedInteger, SynthesisError>
+    where
+        F: Fn(&mut CS, &AllocatedBit, &AllocatedBit) -> Result<AllocatedBit, SynthesisError>,
+    {
+        assert_eq!(self.num_bits(), other.num_bits());
+        let mut bits = Vec::new();
+        for (a, b) in self.bits.iter().zip(other.bits.iter()) {
+            bits.push(f(&mut *cs, a, b)?);
+        }
+        Ok(Self::from_bits(bits))
+    }
+
+    // 1 constraint per bit
+    pub fn and<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        self.bitwise(cs, other, |cs, a, b| AllocatedBit::and(cs, a, b))
+    }
+
+    // 2 constraints per bit, a | b == !(!a & !b)
+    pub fn or<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        self.bitwise(cs, other, |cs, a, b| {
+            let nor = AllocatedBit::nor(&mut *cs, a, b)?;
+            AllocatedBit::nor(&mut *cs, &nor, &nor)
+        })
+    }
+
+    // 1 constraint per bit
+    pub fn xor<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &UnsignedInteger,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        self.bitwise(cs, other, |cs, a, b| AllocatedBit::xor(cs, a, b))
+    }
+
+    // 1 constraint per bit, !a == !a & !a
+    pub fn not<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        self.bitwise(cs, self, |cs, a, _| AllocatedBit::nor(cs, a, a))
+    }
+
+    // A bit that is constrained to be zero, 1 constraint, a & !a == 0
+    pub(super) fn zero_bit<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<AllocatedBit, SynthesisError> {
+        AllocatedBit::and_not(cs, &self.bits[0], &self.bits[0])
+    }
+
+    // Shifts towards the most significant bit, keeping the width, 1 constraint
+    pub fn shl<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        by: usize,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        if by == 0 || self.bits.is_empty() {
+            return Ok(self.clone());
+        }
+        let by = std::cmp::min(by, self.num_bits());
+        let zero = self.zero_bit(cs)?;
+        let mut bits = vec![zero; by];
+        bits.extend(self.bits[..self.num_bits() - by].iter().cloned());
+        Ok(Self::from_bits(bits))
+    }
+
+    // Shifts towards the least significant bit, keeping the width, 1 constraint
+    pub fn shr<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        by: usize,
+    ) -> Result<UnsignedInteger, SynthesisError> {
+        if by == 0 || self.bits.is_empty() {
+            return Ok(self.clone());
+        }
+        let by = std::cmp::min(by, self.num_bits());
+        let zero = self.zero_bit(cs)?;
+        let mut bits = self.bits[by..].to_vec();
+        bits.extend(vec![zero; by]);
+        Ok(Self::from_bits(bits))
+    }
+
+    // Bits are only reordered, 0 constraints
+    pub fn rotl(&self, by: usize) -> UnsignedInteger {
+        let mut bits = self.bits.clone();
+        if !bits.is_empty() {
+            let by = by % bits.len();
+            bits.rotate_right(by);
+        }
+        Self::from_bits(bits)
+    }
+
+    // Bits are only reordered, 0 constraints
+    pub fn rotr(&self, by: usize) -> UnsignedInteger {
+        let mut bits = self.bits.clone();
+        if !bits.is_empty() {
+            let by = by % bits.len();
+            bits.rotate_left(by);
+        }
+        Self::from_bits(bits)
+    }
+}
+
+// a < b, for numbers that are already known to fit in num_bits bits
+pub(super) fn lt_numbers<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    a: &Number,
+    b: &Number,
+    num_bits: usize,
+) -> Result<Boolean, SynthesisError> {
+    // Imagine a and b are two sigend (num_bits + 1) bits numbers
+    let sub = a.clone() - b.clone() + Number::from(pow2(num_bits + 1));
+
+    let sub_bits = UnsignedInteger::constrain(&mut *cs, sub, num_bits + 2)?;
+    Ok(Boolean::Is(sub_bits.bits()[num_bits].clone()))
+}
+
+pub(super) fn to_u128(v: BellmanFr) -> u128 {
+    v.to_le_bits()
+        .iter()
+        .take(128)
+        .rev()
+        .fold(0, |acc, b| (acc << 1) | (*b as u128))
+}
+
+pub(super) fn u128_to_fr(v: u128) -> BellmanFr {
+    BellmanFr::from((v >> 64) as u64) * pow2(64) + BellmanFr::from(v as u64)
+}
+
+pub(super) fn pow2(n: usize) -> BellmanFr {
+    BellmanFr::from(2).pow_vartime(&[n as u64, 0, 0, 0])
 }
@@ -0,0 +1,58 @@
+use super::*;
+use crate::BellmanFr;
+use bazuka::zk::ZkScalar;
+use bellman::gadgets::boolean::AllocatedBit;
+use bellman::{ConstraintSystem, SynthesisError};
+
+// A byte, for composing byte strings in-circuit (E.g. serialized transactions)
+#[derive(Clone)]
+pub struct UInt8 {
+    inner: UnsignedInteger,
+}
+
+impl UInt8 {
+    pub fn get_number(&self) -> &Number {
+        self.inner.get_number()
+    }
+    pub fn get_value(&self) -> Option<u8> {
+        self.inner.get_value().map(|v| to_u128(v) as u8)
+    }
+    // Little-endian
+    pub fn bits(&self) -> &Vec<AllocatedBit> {
+        self.inner.bits()
+    }
+    pub fn as_unsigned(&self) -> &UnsignedInteger {
+        &self.inner
+    }
+    // Fails if `u` is not 8 bits wide, 0 constraints
+    pub fn from_unsigned(u: UnsignedInteger) -> Result<Self, SynthesisError> {
+        if u.num_bits() != 8 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Ok(Self { inner: u })
+    }
+    // 8 constraints
+    pub fn alloc<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        val: u8,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            inner: UnsignedInteger::alloc(cs, ZkScalar::from(val as u64), 8)?,
+        })
+    }
+    pub fn alloc_many<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        vals: &[u8],
+    ) -> Result<Vec<Self>, SynthesisError> {
+        vals.iter().map(|v| Self::alloc(&mut *cs, *v)).collect()
+    }
+    // 9 constraints
+    pub fn constrain<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        num: Number,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            inner: UnsignedInteger::constrain(cs, num, 8)?,
+        })
+    }
+}
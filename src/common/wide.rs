@@ -0,0 +1,221 @@
+use super::*;
+use crate::BellmanFr;
+use bellman::gadgets::boolean::{AllocatedBit, Boolean};
+use bellman::{ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+// Unsigned integer that may not fit in a single field element (E.g. 256-bit EVM
+// amounts). Stored as little-endian limbs of LIMB_BITS bits, only the last limb
+// may be narrower.
+#[derive(Clone)]
+pub struct WideUnsignedInteger {
+    limbs: Vec<UnsignedInteger>,
+}
+
+impl WideUnsignedInteger {
+    pub const LIMB_BITS: usize = 128;
+
+    pub fn limbs(&self) -> &Vec<UnsignedInteger> {
+        &self.limbs
+    }
+    pub fn num_bits(&self) -> usize {
+        self.limbs.iter().map(|l| l.num_bits()).sum()
+    }
+    // Little-endian 64-bit words
+    pub fn get_words(&self) -> Option<Vec<u64>> {
+        let mut words = Vec::new();
+        for limb in self.limbs.iter() {
+            let v = to_u128(limb.get_value()?);
+            words.extend([v as u64, (v >> 64) as u64]);
+        }
+        words.truncate(self.num_bits().div_ceil(64));
+        Some(words)
+    }
+
+    fn from_bits(bits: Vec<AllocatedBit>) -> Self {
+        Self {
+            limbs: bits
+                .chunks(Self::LIMB_BITS)
+                .map(|c| UnsignedInteger::from_bits(c.to_vec()))
+                .collect(),
+        }
+    }
+    fn bits(&self) -> Vec<AllocatedBit> {
+        self.limbs.iter().flat_map(|l| l.bits().clone()).collect()
+    }
+
+    // `words` are little-endian 64-bit words
+    // Unsatisfiable if the value does not fit in num_bits, num_bits constraints
+    pub fn alloc<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        words: &[u64],
+        num_bits: usize,
+    ) -> Result<Self, SynthesisError> {
+        let bit = |i: usize| words.get(i / 64).map(|w| (w >> (i % 64)) & 1 == 1);
+        if (num_bits..words.len() * 64).any(|i| bit(i) == Some(true)) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let mut limbs = Vec::new();
+        for start in (0..num_bits).step_by(Self::LIMB_BITS) {
+            let len = std::cmp::min(Self::LIMB_BITS, num_bits - start);
+            let val = (0..len).fold(0u128, |acc, i| {
+                acc | ((bit(start + i).unwrap_or(false) as u128) << i)
+            });
+            let (bits, _) = UnsignedInteger::alloc_bits(&mut *cs, Some(u128_to_fr(val)), len)?;
+            limbs.push(UnsignedInteger::from_bits(bits));
+        }
+        Ok(Self { limbs })
+    }
+    pub fn alloc_128<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        val: u128,
+    ) -> Result<Self, SynthesisError> {
+        Self::alloc(cs, &[val as u64, (val >> 64) as u64], 128)
+    }
+    pub fn alloc_u256<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        val: [u64; 4],
+    ) -> Result<Self, SynthesisError> {
+        Self::alloc(cs, &val, 256)
+    }
+
+    // Resizes `u` to num_bits. Narrower values are padded with zero bits (1 constraint),
+    // for wider values the extra bits are constrained to zero (1 constraint), so the
+    // circuit is unsatisfiable if the value does not fit.
+    pub fn from_unsigned<CS: ConstraintSystem<BellmanFr>>(
+        cs: &mut CS,
+        u: &UnsignedInteger,
+        num_bits: usize,
+    ) -> Result<Self, SynthesisError> {
+        assert!(u.num_bits() > 0);
+        let mut bits = u.bits().clone();
+        if bits.len() < num_bits {
+            let zero = u.zero_bit(cs)?;
+            bits.resize(num_bits, zero);
+        } else if bits.len() > num_bits {
+            let rest = bits
+                .drain(num_bits..)
+                .fold(Number::zero(), |acc, b| acc + Number::from(b));
+            rest.assert_equal(cs, &Number::zero());
+        }
+        Ok(Self::from_bits(bits))
+    }
+
+    // Returns (self + other + carry) mod 2^num_bits and the carry out,
+    // LIMB_BITS + 2 constraints per limb
+    pub fn adc<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &WideUnsignedInteger,
+        carry: &Boolean,
+    ) -> Result<(WideUnsignedInteger, Boolean), SynthesisError> {
+        assert_eq!(self.num_bits(), other.num_bits());
+        let mut carry = carry.clone();
+        let mut limbs = Vec::new();
+        for (a, b) in self.limbs.iter().zip(other.limbs.iter()) {
+            let sum = a.get_number().clone() + b.get_number().clone() + Number::from(carry);
+            let mut sum_bits = UnsignedInteger::constrain(&mut *cs, sum, a.num_bits() + 1)?
+                .bits()
+                .clone();
+            carry = Boolean::Is(sum_bits.pop().unwrap());
+            limbs.push(UnsignedInteger::from_bits(sum_bits));
+        }
+        Ok((WideUnsignedInteger { limbs }, carry))
+    }
+
+    pub fn checked_add<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &WideUnsignedInteger,
+    ) -> Result<(WideUnsignedInteger, Boolean), SynthesisError> {
+        self.adc(cs, other, &Boolean::Constant(false))
+    }
+
+    // Unsatisfiable on overflow
+    pub fn add<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &WideUnsignedInteger,
+    ) -> Result<WideUnsignedInteger, SynthesisError> {
+        let (sum, carry) = self.checked_add(&mut *cs, other)?;
+        assert_true(&mut *cs, &carry.not());
+        Ok(sum)
+    }
+
+    // 3 constraints per limb
+    pub fn is_equal<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &WideUnsignedInteger,
+    ) -> Result<Boolean, SynthesisError> {
+        assert_eq!(self.num_bits(), other.num_bits());
+        let mut is_eq = Boolean::Constant(true);
+        for (a, b) in self.limbs.iter().zip(other.limbs.iter()) {
+            let limb_eq = a.get_number().is_equal(&mut *cs, b.get_number())?;
+            is_eq = Boolean::and(&mut *cs, &is_eq, &limb_eq)?;
+        }
+        Ok(is_eq)
+    }
+
+    pub fn assert_equal<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &WideUnsignedInteger,
+    ) {
+        assert_eq!(self.num_bits(), other.num_bits());
+        for (a, b) in self.limbs.iter().zip(other.limbs.iter()) {
+            a.get_number().assert_equal(&mut *cs, b.get_number());
+        }
+    }
+
+    // Compared from the least significant limb upwards, a more significant limb
+    // decides unless it is equal. LIMB_BITS + 7 constraints per limb
+    pub fn lt<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &WideUnsignedInteger,
+    ) -> Result<Boolean, SynthesisError> {
+        assert_eq!(self.num_bits(), other.num_bits());
+        let mut is_lt = Boolean::Constant(false);
+        for (a, b) in self.limbs.iter().zip(other.limbs.iter()) {
+            let limb_lt = a.lt(&mut *cs, b)?;
+            let limb_eq = a.get_number().is_equal(&mut *cs, b.get_number())?;
+            let lower_lt = Boolean::and(&mut *cs, &limb_eq, &is_lt)?;
+            is_lt = boolean_or(&mut *cs, &limb_lt, &lower_lt)?;
+        }
+        Ok(is_lt)
+    }
+
+    pub fn gt<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &WideUnsignedInteger,
+    ) -> Result<Boolean, SynthesisError> {
+        other.lt(cs, self)
+    }
+
+    pub fn lte<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &WideUnsignedInteger,
+    ) -> Result<Boolean, SynthesisError> {
+        Ok(self.gt(cs, other)?.not())
+    }
+
+    pub fn gte<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+        other: &WideUnsignedInteger,
+    ) -> Result<Boolean, SynthesisError> {
+        Ok(self.lt(cs, other)?.not())
+    }
+
+    // Packs the bits into a single UnsignedInteger, 0 constraints. Fails when the
+    // width does not fit in a field element.
+    pub fn to_unsigned(&self) -> Result<UnsignedInteger, SynthesisError> {
+        if self.num_bits() >= BellmanFr::CAPACITY as usize {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Ok(UnsignedInteger::from_bits(self.bits()))
+    }
+}
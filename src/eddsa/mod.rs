@@ -70,7 +70,7 @@ impl AllocatedPoint {
         let y2 = self.y.mul(&mut *cs, &self.y)?;
         let x2y2 = x2.mul(&mut *cs, &y2)?;
         let lhs = Number::from(y2) - Number::from(x2);
-        let rhs = Number::from((BellmanFr::from(*D), x2y2)) + Number::one::<CS>();
+        let rhs = Number::from((BellmanFr::from(*D), x2y2)) + Number::from(BellmanFr::one());
         lhs.assert_equal_if_enabled(cs, enabled, &rhs)
     }
 
@@ -177,12 +177,15 @@ impl AllocatedPoint {
         b: &AllocatedNum<BellmanFr>,
     ) -> Result<AllocatedPoint, SynthesisError> {
         let bits: Vec<Boolean> = b.to_bits_le_strict(&mut *cs)?.into_iter().rev().collect();
+        // Unlike `base_mul`, one option of the first selection is a variable, so
+        // `mux_number` could not fold it, and `add` needs allocated coordinates,
+        // 1 constraint per coordinate
         let mut result = AllocatedPoint {
             x: common::mux(&mut *cs, &bits[0], &Number::zero(), &self.x.clone().into())?,
             y: common::mux(
                 &mut *cs,
                 &bits[0],
-                &Number::constant::<CS>(BellmanFr::one()),
+                &Number::from(BellmanFr::one()),
                 &self.y.clone().into(),
             )?,
         };
@@ -202,31 +205,54 @@ impl AllocatedPoint {
     }
 }
 
+// The first two bits select one of O, B, 2B, 3B, which are all constants, so the
+// first doubling and addition fold into linear combinations: 2 constraints for the
+// first two bits, then 14 constraints per bit
 pub fn base_mul<CS: ConstraintSystem<BellmanFr>>(
     cs: &mut CS,
     base: &PointAffine,
     b: &AllocatedNum<BellmanFr>,
 ) -> Result<AllocatedPoint, SynthesisError> {
     let bits: Vec<Boolean> = b.to_bits_le_strict(&mut *cs)?.into_iter().rev().collect();
+    let mut double = *base;
+    double.add_assign(base);
+    let mut triple = double;
+    triple.add_assign(base);
+
+    // bits[0] ? 2B : O, and bits[0] ? 3B : B
+    let even_x = common::mux_number(
+        &mut *cs,
+        &bits[0],
+        &Number::zero(),
+        &Number::from(BellmanFr::from(double.0)),
+    )?;
+    let even_y = common::mux_number(
+        &mut *cs,
+        &bits[0],
+        &Number::from(BellmanFr::one()),
+        &Number::from(BellmanFr::from(double.1)),
+    )?;
+    let odd_x = common::mux_number(
+        &mut *cs,
+        &bits[0],
+        &Number::from(BellmanFr::from(base.0)),
+        &Number::from(BellmanFr::from(triple.0)),
+    )?;
+    let odd_y = common::mux_number(
+        &mut *cs,
+        &bits[0],
+        &Number::from(BellmanFr::from(base.1)),
+        &Number::from(BellmanFr::from(triple.1)),
+    )?;
     let mut result = AllocatedPoint {
-        x: common::mux(
-            &mut *cs,
-            &bits[0],
-            &Number::zero(),
-            &Number::constant::<CS>(base.0.into()),
-        )?,
-        y: common::mux(
-            &mut *cs,
-            &bits[0],
-            &Number::constant::<CS>(BellmanFr::one()),
-            &Number::constant::<CS>(base.1.into()),
-        )?,
+        x: common::mux(&mut *cs, &bits[1], &even_x, &odd_x)?,
+        y: common::mux(&mut *cs, &bits[1], &even_y, &odd_y)?,
     };
-    for bit in bits[1..].iter() {
+    for bit in bits[2..].iter() {
         result = result.add(&mut *cs, &result)?;
         let result_plus_base = result.add_const(&mut *cs, base)?;
-        let result_x = common::mux(&mut *cs, &bit, &result.x.into(), &result_plus_base.x.into())?;
-        let result_y = common::mux(&mut *cs, &bit, &result.y.into(), &result_plus_base.y.into())?;
+        let result_x = common::mux(&mut *cs, bit, &result.x.into(), &result_plus_base.x.into())?;
+        let result_y = common::mux(&mut *cs, bit, &result.y.into(), &result_plus_base.y.into())?;
         result = AllocatedPoint {
             x: result_x,
             y: result_y,
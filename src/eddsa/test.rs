@@ -1,4 +1,5 @@
 use super::*;
+use crate::test_utils::CountingConstraintSystem;
 use crate::Bls12;
 use bazuka::core::ZkHasher;
 use bazuka::crypto::jubjub::*;
@@ -7,6 +8,7 @@ use bazuka::zk::ZkScalar;
 use bellman::gadgets::boolean::{AllocatedBit, Boolean};
 use bellman::gadgets::num::AllocatedNum;
 use bellman::{groth16, Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
 use rand::rngs::OsRng;
 
 struct TestEddsaVerify {
@@ -94,3 +96,28 @@ fn test_eddsa_verify() {
     let proof = groth16::create_random_proof(c, &params, &mut OsRng).unwrap();
     assert!(groth16::verify_proof(&pvk, &proof, &[]).is_ok());
 }
+
+#[test]
+fn test_base_mul_constraints() {
+    for scalar in [0, 1, 2, 3, 4, 5, 1234567] {
+        let mut cs = CountingConstraintSystem::new();
+        let b = AllocatedNum::alloc(&mut cs, || Ok(BellmanFr::from(scalar))).unwrap();
+        b.to_bits_le_strict(&mut cs).unwrap();
+        let bits_constraints = cs.num_constraints;
+
+        let res = base_mul(&mut cs, &BASE, &b).unwrap();
+        assert_eq!(
+            res.get_value(),
+            Some(BASE.multiply(&ZkScalar::from(scalar)))
+        );
+        assert!(cs.satisfied);
+
+        // The bits, 2 for the first two bits and 14 for each other bit,
+        // folding the constant first selection saves a doubling and an addition
+        let num_bits = BellmanFr::NUM_BITS as usize;
+        assert_eq!(
+            cs.num_constraints - bits_constraints,
+            bits_constraints + 2 + 14 * (num_bits - 2)
+        );
+    }
+}
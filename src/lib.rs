@@ -1,7 +1,13 @@
 pub use bls12_381::{Bls12, G1Affine as BellmanG1, G2Affine as BellmanG2, Scalar as BellmanFr};
 
+pub mod balance;
 pub mod common;
 pub mod eddsa;
 pub mod merkle;
+pub mod note;
+pub mod pedersen;
 pub mod poseidon;
 pub mod reveal;
+
+#[cfg(test)]
+mod test_utils;
@@ -10,7 +10,7 @@ impl<const LOG4_TREE_SIZE: u8> Default for Proof<LOG4_TREE_SIZE> {
     }
 }
 
-use crate::common::{boolean_or, Number};
+use crate::common::Number;
 use crate::BellmanFr;
 use crate::{common, poseidon};
 
@@ -25,30 +25,22 @@ fn merge_hash_poseidon4<CS: ConstraintSystem<BellmanFr>>(
     v: &Number,
     p: &[AllocatedNum<BellmanFr>; 3],
 ) -> Result<Number, SynthesisError> {
-    let select = (Boolean::Is(select.0.clone()), Boolean::Is(select.1.clone()));
-
-    let and = Boolean::and(&mut *cs, &select.0, &select.1)?;
-    let or = boolean_or(&mut *cs, &select.0, &select.1)?;
-
-    // v0 == s0_or_s1 ? p[0] : v
-    let v0 = common::mux(&mut *cs, &or, v, &p[0].clone().into())?;
-
-    //v1p == s0 ? v : p[0]
-    let v1p = common::mux(&mut *cs, &select.0, &p[0].clone().into(), v)?;
-
-    //v1 == s1 ? p[1] : v1p
-    let v1 = common::mux(&mut *cs, &select.1, &v1p.into(), &p[1].clone().into())?;
-
-    //v2p == s0 ? p[2] : v
-    let v2p = common::mux(&mut *cs, &select.0, v, &p[2].clone().into())?;
-
-    //v2 == s1 ? v2p : p[1]
-    let v2 = common::mux(&mut *cs, &select.1, &p[1].clone().into(), &v2p.into())?;
-
-    //v3 == s0_and_s1 ? v : p[2]
-    let v3 = common::mux(&mut *cs, &and, &p[2].clone().into(), &v)?;
+    let index = [Boolean::Is(select.0.clone()), Boolean::Is(select.1.clone())];
+    let p: Vec<Number> = p.iter().map(|p| p.clone().into()).collect();
+
+    // v is placed at the given index, the siblings fill the rest of the slots in order
+    let slots = [
+        [v, &p[0], &p[0], &p[0]],
+        [&p[0], v, &p[1], &p[1]],
+        [&p[1], &p[1], v, &p[2]],
+        [&p[2], &p[2], &p[2], v],
+    ];
+    let mut vals = Vec::new();
+    for slot in slots {
+        vals.push(common::mux_n(&mut *cs, &index, &slot.map(|o| o.clone()))?);
+    }
 
-    poseidon::poseidon(cs, &[&v0.into(), &v1.into(), &v2.into(), &v3.into()])
+    poseidon::poseidon(cs, &[&vals[0], &vals[1], &vals[2], &vals[3]])
 }
 
 pub fn calc_root_poseidon4<CS: ConstraintSystem<BellmanFr>>(
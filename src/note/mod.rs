@@ -0,0 +1,122 @@
+use crate::common::{Number, UnsignedInteger};
+use crate::eddsa::{base_mul, AllocatedPoint};
+use crate::BellmanFr;
+use crate::{merkle, poseidon};
+
+use bazuka::crypto::jubjub::{PointAffine, BASE};
+use bazuka::zk::ZkScalar;
+
+use bellman::gadgets::boolean::Boolean;
+use bellman::gadgets::num::AllocatedNum;
+use bellman::{ConstraintSystem, SynthesisError};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Note {
+    pub pub_key: PointAffine,
+    // Has to fit in 64 bits, `AllocatedNote::alloc` fails otherwise
+    pub amount: ZkScalar,
+    pub token_id: ZkScalar,
+    pub salt: ZkScalar,
+}
+
+impl Note {
+    // Poseidon(pub_key.x, pub_key.y, amount, token_id, salt)
+    pub fn commitment(&self) -> ZkScalar {
+        bazuka::zk::poseidon::poseidon(&[
+            self.pub_key.0,
+            self.pub_key.1,
+            self.amount,
+            self.token_id,
+            self.salt,
+        ])
+    }
+}
+
+// The owner of a note is the one who knows secret_key, where pub_key == secret_key * BASE
+pub fn public_key(secret_key: ZkScalar) -> PointAffine {
+    BASE.multiply(&secret_key)
+}
+
+// Poseidon(secret_key, position), position is the index of the commitment in the tree
+pub fn nullifier(secret_key: ZkScalar, position: u64) -> ZkScalar {
+    bazuka::zk::poseidon::poseidon(&[secret_key, ZkScalar::from(position)])
+}
+
+#[derive(Clone)]
+pub struct AllocatedNote {
+    pub pub_key: AllocatedPoint,
+    pub amount: UnsignedInteger,
+    pub token_id: Number,
+    pub salt: Number,
+}
+
+impl AllocatedNote {
+    // The amount is range checked to 64 bits, an amount that does not fit fails
+    // with `SynthesisError::Unsatisfiable`. Its value is not needed for generating
+    // the parameters, so a missing note is allocated as a zero amount there.
+    pub fn alloc<CS: ConstraintSystem<BellmanFr>, F: Fn() -> Result<Note, SynthesisError>>(
+        cs: &mut CS,
+        f: F,
+    ) -> Result<AllocatedNote, SynthesisError> {
+        let pub_key = AllocatedPoint::alloc(&mut *cs, || Ok(f()?.pub_key))?;
+        let amount = f().map(|n| n.amount).unwrap_or_default();
+        let amount = UnsignedInteger::alloc(&mut *cs, amount, 64)?;
+        let token_id = AllocatedNum::alloc(&mut *cs, || Ok(f()?.token_id.into()))?;
+        let salt = AllocatedNum::alloc(&mut *cs, || Ok(f()?.salt.into()))?;
+        Ok(Self {
+            pub_key,
+            amount,
+            token_id: token_id.into(),
+            salt: salt.into(),
+        })
+    }
+
+    pub fn commitment<CS: ConstraintSystem<BellmanFr>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Number, SynthesisError> {
+        poseidon::poseidon(
+            cs,
+            &[
+                &self.pub_key.x.clone().into(),
+                &self.pub_key.y.clone().into(),
+                self.amount.get_number(),
+                &self.token_id,
+                &self.salt,
+            ],
+        )
+    }
+}
+
+// Proves that the note is owned by secret_key and that its commitment is in the
+// tree at `index`, and returns the nullifier of the note. Nothing is checked when
+// `enabled` is false.
+pub fn spend_note<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    enabled: &Boolean,
+    note: &AllocatedNote,
+    secret_key: &AllocatedNum<BellmanFr>,
+    index: &UnsignedInteger,
+    proof: &[[AllocatedNum<BellmanFr>; 3]],
+    root: &Number,
+) -> Result<Number, SynthesisError> {
+    let pub_key = base_mul(&mut *cs, &BASE, secret_key)?;
+    Number::from(pub_key.x).assert_equal_if_enabled(
+        &mut *cs,
+        enabled,
+        &note.pub_key.x.clone().into(),
+    )?;
+    Number::from(pub_key.y).assert_equal_if_enabled(
+        &mut *cs,
+        enabled,
+        &note.pub_key.y.clone().into(),
+    )?;
+
+    let commitment = note.commitment(&mut *cs)?;
+    merkle::check_proof_poseidon4(&mut *cs, enabled, index, &commitment, proof, root)?;
+
+    poseidon::poseidon(cs, &[&secret_key.clone().into(), index.get_number()])
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,180 @@
+use super::*;
+use crate::Bls12;
+use bellman::gadgets::boolean::AllocatedBit;
+use bellman::{groth16, Circuit};
+use rand::rngs::OsRng;
+
+#[derive(Clone)]
+struct TestSpendNoteCircuit {
+    enabled: Option<bool>,
+    note: Option<Note>,
+    secret_key: Option<BellmanFr>,
+    index: Option<BellmanFr>,
+    proof: [Option<BellmanFr>; 3],
+    root: Option<BellmanFr>,
+    nullifier: Option<BellmanFr>,
+}
+
+impl Circuit<BellmanFr> for TestSpendNoteCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let enabled = Boolean::Is(AllocatedBit::alloc(&mut *cs, self.enabled)?);
+        let note = AllocatedNote::alloc(&mut *cs, || {
+            self.note.clone().ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let secret_key = AllocatedNum::alloc(&mut *cs, || {
+            self.secret_key.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let index = AllocatedNum::alloc(&mut *cs, || {
+            self.index.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let index = UnsignedInteger::constrain(&mut *cs, index.into(), 2)?;
+        let mut proof = Vec::new();
+        for p in self.proof.iter() {
+            proof.push(AllocatedNum::alloc(&mut *cs, || {
+                p.ok_or(SynthesisError::AssignmentMissing)
+            })?);
+        }
+        let proof = [[proof[0].clone(), proof[1].clone(), proof[2].clone()]];
+        let root = AllocatedNum::alloc(&mut *cs, || {
+            self.root.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let nullifier = AllocatedNum::alloc(&mut *cs, || {
+            self.nullifier.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let res = spend_note(
+            &mut *cs,
+            &enabled,
+            &note,
+            &secret_key,
+            &index,
+            &proof,
+            &root.into(),
+        )?;
+        res.assert_equal(&mut *cs, &nullifier.into());
+
+        Ok(())
+    }
+}
+
+// Root of a single level tree, with the commitment at `position`
+fn calc_root(commitment: ZkScalar, position: usize, siblings: &[ZkScalar; 3]) -> ZkScalar {
+    let mut leaves = siblings.to_vec();
+    leaves.insert(position, commitment);
+    bazuka::zk::poseidon::poseidon(&leaves)
+}
+
+#[test]
+fn test_note_native() {
+    let note = Note {
+        pub_key: public_key(ZkScalar::from(1234)),
+        amount: ZkScalar::from(100),
+        token_id: ZkScalar::from(1),
+        salt: ZkScalar::from(5678),
+    };
+    let mut other = note.clone();
+    other.salt = ZkScalar::from(5679);
+    assert_ne!(note.commitment(), other.commitment());
+    assert_ne!(
+        nullifier(ZkScalar::from(1234), 0),
+        nullifier(ZkScalar::from(1234), 1)
+    );
+    assert_ne!(
+        nullifier(ZkScalar::from(1234), 0),
+        nullifier(ZkScalar::from(1235), 0)
+    );
+}
+
+#[test]
+fn test_spend_note_circuit() {
+    let params = {
+        let c = TestSpendNoteCircuit {
+            enabled: None,
+            note: None,
+            secret_key: None,
+            index: None,
+            proof: [None; 3],
+            root: None,
+            nullifier: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    let secret_key = ZkScalar::from(1234);
+    let note = Note {
+        pub_key: public_key(secret_key),
+        amount: ZkScalar::from(100),
+        token_id: ZkScalar::from(1),
+        salt: ZkScalar::from(5678),
+    };
+    let siblings = [11, 22, 33].map(ZkScalar::from);
+
+    for position in 0..4 {
+        let root = calc_root(note.commitment(), position, &siblings);
+        let correct = TestSpendNoteCircuit {
+            enabled: Some(true),
+            note: Some(note.clone()),
+            secret_key: Some(secret_key.into()),
+            index: Some(BellmanFr::from(position as u64)),
+            proof: siblings.map(|s| Some(s.into())),
+            root: Some(root.into()),
+            nullifier: Some(nullifier(secret_key, position as u64).into()),
+        };
+
+        let mut wrong_key = correct.clone();
+        wrong_key.secret_key = Some(BellmanFr::from(1235));
+        wrong_key.nullifier = Some(nullifier(ZkScalar::from(1235), position as u64).into());
+
+        let mut wrong_position = correct.clone();
+        wrong_position.index = Some(BellmanFr::from(((position + 1) % 4) as u64));
+        wrong_position.nullifier = Some(nullifier(secret_key, ((position + 1) % 4) as u64).into());
+
+        let mut wrong_nullifier = correct.clone();
+        wrong_nullifier.nullifier = Some(nullifier(secret_key, position as u64 + 1).into());
+
+        let mut wrong_amount = correct.clone();
+        wrong_amount.note.as_mut().unwrap().amount = ZkScalar::from(101);
+
+        let mut disabled = wrong_position.clone();
+        disabled.enabled = Some(false);
+
+        for (c, expected) in [
+            (correct, true),
+            (wrong_key, false),
+            (wrong_position, false),
+            (wrong_nullifier, false),
+            (wrong_amount, false),
+            (disabled, true),
+        ] {
+            let proof = groth16::create_random_proof(c, &params, &mut OsRng).unwrap();
+            assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+        }
+    }
+
+    // Amounts are limited to 64 bits
+    let max = ZkScalar::from(u64::MAX);
+    for (amount, expected) in [(max, true), (max + ZkScalar::from(1), false)] {
+        let mut note = note.clone();
+        note.amount = amount;
+        let root = calc_root(note.commitment(), 0, &siblings);
+        let c = TestSpendNoteCircuit {
+            enabled: Some(true),
+            note: Some(note),
+            secret_key: Some(secret_key.into()),
+            index: Some(BellmanFr::from(0)),
+            proof: siblings.map(|s| Some(s.into())),
+            root: Some(root.into()),
+            nullifier: Some(nullifier(secret_key, 0).into()),
+        };
+        match groth16::create_random_proof(c, &params, &mut OsRng) {
+            Ok(proof) => assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected),
+            Err(SynthesisError::Unsatisfiable) => assert!(!expected),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+}
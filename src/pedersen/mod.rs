@@ -0,0 +1,89 @@
+use crate::eddsa::{base_mul, AllocatedPoint};
+use crate::BellmanFr;
+
+use bazuka::crypto::jubjub::{PointAffine, A, D};
+use bazuka::zk::ZkScalar;
+
+use bellman::gadgets::num::AllocatedNum;
+use bellman::{ConstraintSystem, SynthesisError};
+use ff::{Field, PrimeField};
+use lazy_static::lazy_static;
+
+// Try-and-increment: y = Poseidon(len(domain), domain as u64 words..., counter) until
+// there is an x on the curve (The one with even repr is taken), and then multiplied
+// by the cofactor so that the point lands in the prime order subgroup. Nobody knows
+// the discrete log of the result relative to any other generator.
+pub fn hash_to_curve(domain: &[u8]) -> PointAffine {
+    let mut inputs = vec![ZkScalar::from(domain.len() as u64)];
+    for chunk in domain.chunks(8) {
+        let mut word = [0u8; 8];
+        word[..chunk.len()].copy_from_slice(chunk);
+        inputs.push(ZkScalar::from(u64::from_le_bytes(word)));
+    }
+    let a: BellmanFr = (*A).into();
+    let d: BellmanFr = (*D).into();
+    let mut counter = 0u64;
+    loop {
+        let mut preimage = inputs.clone();
+        preimage.push(ZkScalar::from(counter));
+        counter += 1;
+
+        // a * x^2 + y^2 = 1 + d * x^2 * y^2  =>  x^2 = (1 - y^2) / (a - d * y^2)
+        let y: BellmanFr = bazuka::zk::poseidon::poseidon(&preimage).into();
+        let y2 = y.square();
+        let x2 = match Option::<BellmanFr>::from((a - d * y2).invert()) {
+            Some(inv) => (BellmanFr::one() - y2) * inv,
+            None => continue,
+        };
+        let mut x = match Option::<BellmanFr>::from(x2.sqrt()) {
+            Some(x) => x,
+            None => continue,
+        };
+        if x.to_repr().as_ref()[0] & 1 == 1 {
+            x = -x;
+        }
+
+        let pnt = PointAffine(x.into(), y.into()).multiply(&ZkScalar::from(8));
+        if pnt != PointAffine::zero() {
+            return pnt;
+        }
+    }
+}
+
+lazy_static! {
+    // Generator of the committed value
+    pub static ref VALUE_GENERATOR: PointAffine = hash_to_curve(b"zeekit/pedersen/value");
+    // Generator of the blinding factor
+    pub static ref RANDOMNESS_GENERATOR: PointAffine =
+        hash_to_curve(b"zeekit/pedersen/randomness");
+}
+
+// value * G + randomness * H
+pub fn commit_native(value: ZkScalar, randomness: ZkScalar) -> PointAffine {
+    let mut res = VALUE_GENERATOR.multiply(&value);
+    res.add_assign(&RANDOMNESS_GENERATOR.multiply(&randomness));
+    res
+}
+
+// value * G + randomness * H, value is not range checked here
+pub fn commit<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    value: &AllocatedNum<BellmanFr>,
+    randomness: &AllocatedNum<BellmanFr>,
+) -> Result<AllocatedPoint, SynthesisError> {
+    let value_part = base_mul(&mut *cs, &VALUE_GENERATOR, value)?;
+    let randomness_part = base_mul(&mut *cs, &RANDOMNESS_GENERATOR, randomness)?;
+    value_part.add(&mut *cs, &randomness_part)
+}
+
+// commit(v1, r1) + commit(v2, r2) == commit(v1 + v2, r1 + r2)
+pub fn add_commitments<CS: ConstraintSystem<BellmanFr>>(
+    cs: &mut CS,
+    a: &AllocatedPoint,
+    b: &AllocatedPoint,
+) -> Result<AllocatedPoint, SynthesisError> {
+    a.add(cs, b)
+}
+
+#[cfg(test)]
+mod test;
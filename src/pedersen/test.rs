@@ -0,0 +1,91 @@
+use super::*;
+use crate::common::Number;
+use crate::Bls12;
+use bazuka::crypto::jubjub::BASE;
+use bellman::{groth16, Circuit};
+use rand::rngs::OsRng;
+
+#[derive(Clone)]
+struct TestCommitCircuit {
+    values: [Option<BellmanFr>; 2],
+    randomness: [Option<BellmanFr>; 2],
+    sum: Option<PointAffine>,
+}
+
+impl Circuit<BellmanFr> for TestCommitCircuit {
+    fn synthesize<CS: ConstraintSystem<BellmanFr>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let mut commitments = Vec::new();
+        for (v, r) in self.values.iter().zip(self.randomness.iter()) {
+            let v = AllocatedNum::alloc(&mut *cs, || v.ok_or(SynthesisError::AssignmentMissing))?;
+            let r = AllocatedNum::alloc(&mut *cs, || r.ok_or(SynthesisError::AssignmentMissing))?;
+            commitments.push(commit(&mut *cs, &v, &r)?);
+        }
+        let expected = AllocatedPoint::alloc(&mut *cs, || {
+            self.sum.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let sum = add_commitments(&mut *cs, &commitments[0], &commitments[1])?;
+        Number::from(sum.x).assert_equal(&mut *cs, &expected.x.into());
+        Number::from(sum.y).assert_equal(&mut *cs, &expected.y.into());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_generators() {
+    let g = *VALUE_GENERATOR;
+    let h = *RANDOMNESS_GENERATOR;
+    assert!(g.is_on_curve() && h.is_on_curve());
+    assert_ne!(g, h);
+    assert_ne!(g, *BASE);
+    assert_ne!(g, PointAffine::zero());
+    assert_ne!(h, PointAffine::zero());
+    assert_eq!(hash_to_curve(b"zeekit/pedersen/value"), g);
+}
+
+#[test]
+fn test_commit_native() {
+    let c = commit_native(ZkScalar::from(100), ZkScalar::from(1234));
+    assert!(c.is_on_curve());
+    assert_ne!(c, commit_native(ZkScalar::from(101), ZkScalar::from(1234)));
+    assert_ne!(c, commit_native(ZkScalar::from(100), ZkScalar::from(1235)));
+
+    let mut sum = commit_native(ZkScalar::from(60), ZkScalar::from(1000));
+    sum.add_assign(&commit_native(ZkScalar::from(40), ZkScalar::from(234)));
+    assert_eq!(sum, c);
+}
+
+#[test]
+fn test_commit_circuit() {
+    let params = {
+        let c = TestCommitCircuit {
+            values: [None; 2],
+            randomness: [None; 2],
+            sum: None,
+        };
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    };
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    for (values, randomness, sum, expected) in [
+        ([60, 40], [1000, 234], (100, 1234), true),
+        ([0, 0], [0, 0], (0, 0), true),
+        ([0, 1], [5, 0], (1, 5), true),
+        ([60, 40], [1000, 234], (101, 1234), false),
+        ([60, 40], [1000, 234], (100, 1233), false),
+        ([60, 40], [1000, 234], (1234, 100), false),
+    ] {
+        let c = TestCommitCircuit {
+            values: values.map(|v| Some(BellmanFr::from(v))),
+            randomness: randomness.map(|r| Some(BellmanFr::from(r))),
+            sum: Some(commit_native(ZkScalar::from(sum.0), ZkScalar::from(sum.1))),
+        };
+        let proof = groth16::create_random_proof(c, &params, &mut OsRng).unwrap();
+        assert_eq!(groth16::verify_proof(&pvk, &proof, &[]).is_ok(), expected);
+    }
+}
@@ -2,25 +2,21 @@ use crate::common::Number;
 use crate::BellmanFr;
 
 use bazuka::zk::poseidon::PoseidonParams;
-use bellman::gadgets::num::AllocatedNum;
 use bellman::{ConstraintSystem, SynthesisError};
 
 fn sbox<CS: ConstraintSystem<BellmanFr>>(
     cs: &mut CS,
     a: &Number,
-) -> Result<AllocatedNum<BellmanFr>, SynthesisError> {
-    let a2 = a.mul(&mut *cs, &a)?;
-    let a4 = a2.mul(&mut *cs, &a2)?;
-    a.mul(&mut *cs, &a4.into())
+) -> Result<Number, SynthesisError> {
+    let a2 = a.mul_number(&mut *cs, a)?;
+    let a4 = a2.mul_number(&mut *cs, &a2)?;
+    a.mul_number(&mut *cs, &a4)
 }
 
-fn add_constants<CS: ConstraintSystem<BellmanFr>>(
-    vals: &mut [Number],
-    const_offset: usize,
-    params: &PoseidonParams,
-) {
+fn add_constants(vals: &mut [Number], const_offset: usize, params: &PoseidonParams) {
     for (i, val) in vals.iter_mut().enumerate() {
-        val.add_constant::<CS>(params.round_constants[const_offset + i].into());
+        *val =
+            val.clone() + Number::from(BellmanFr::from(params.round_constants[const_offset + i]));
     }
 }
 
@@ -30,9 +26,9 @@ fn partial_round<CS: ConstraintSystem<BellmanFr>>(
     mut vals: Vec<Number>,
     params: &PoseidonParams,
 ) -> Result<Vec<Number>, SynthesisError> {
-    add_constants::<CS>(&mut vals, const_offset, params);
+    add_constants(&mut vals, const_offset, params);
 
-    vals[0] = sbox(&mut *cs, &vals[0])?.into();
+    vals[0] = sbox(&mut *cs, &vals[0])?;
     for i in 1..vals.len() {
         vals[i] = vals[i].clone().compress(&mut *cs)?.into();
     }
@@ -46,10 +42,10 @@ fn full_round<CS: ConstraintSystem<BellmanFr>>(
     mut vals: Vec<Number>,
     params: &PoseidonParams,
 ) -> Result<Vec<Number>, SynthesisError> {
-    add_constants::<CS>(&mut vals, const_offset, params);
+    add_constants(&mut vals, const_offset, params);
 
     for val in vals.iter_mut() {
-        *val = sbox(&mut *cs, val)?.into();
+        *val = sbox(&mut *cs, val)?;
     }
 
     product_mds(vals, params)
@@ -0,0 +1,85 @@
+use crate::BellmanFr;
+use bellman::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+
+// Counts the constraints and checks them, without the unique names required by
+// `TestConstraintSystem`, so that unnamespaced gadgets can be measured
+pub struct CountingConstraintSystem {
+    inputs: Vec<BellmanFr>,
+    aux: Vec<BellmanFr>,
+    pub num_constraints: usize,
+    pub satisfied: bool,
+}
+
+impl CountingConstraintSystem {
+    pub fn new() -> Self {
+        Self {
+            inputs: vec![BellmanFr::one()],
+            aux: Vec::new(),
+            num_constraints: 0,
+            satisfied: true,
+        }
+    }
+    fn eval(&self, lc: &LinearCombination<BellmanFr>) -> BellmanFr {
+        lc.as_ref()
+            .iter()
+            .map(|(var, coeff)| match var.get_unchecked() {
+                Index::Input(i) => self.inputs[i] * coeff,
+                Index::Aux(i) => self.aux[i] * coeff,
+            })
+            .sum()
+    }
+}
+
+impl ConstraintSystem<BellmanFr> for CountingConstraintSystem {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<BellmanFr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.aux.push(f()?);
+        Ok(Variable::new_unchecked(Index::Aux(self.aux.len() - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<BellmanFr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inputs.push(f()?);
+        Ok(Variable::new_unchecked(Index::Input(self.inputs.len() - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<BellmanFr>) -> LinearCombination<BellmanFr>,
+        LB: FnOnce(LinearCombination<BellmanFr>) -> LinearCombination<BellmanFr>,
+        LC: FnOnce(LinearCombination<BellmanFr>) -> LinearCombination<BellmanFr>,
+    {
+        let (a, b, c) = (
+            a(LinearCombination::zero()),
+            b(LinearCombination::zero()),
+            c(LinearCombination::zero()),
+        );
+        self.satisfied &= self.eval(&a) * self.eval(&b) == self.eval(&c);
+        self.num_constraints += 1;
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}